@@ -31,8 +31,21 @@ fn transform() -> CwtResult<f32> {
 }
 
 fn criterion_benchmark(c: &mut Criterion) {
-    c.bench_function("fcwt 2048x10", |b| b.iter(|| transform()));
-    c.bench_function("fcwt 2048x10 norm", |b| b.iter(|| transform_normalized()));
+    c.bench_function("fcwt 2048x10", |b| b.iter(transform));
+    c.bench_function("fcwt 2048x10 norm", |b| b.iter(transform_normalized));
+
+    // Repeated transforms on a single instance reuse the cached FFT plan and mother wavelet
+    let w = MorletWavelet::new(2.0);
+    let scales = LinFreqs::new(SAMPLE_RATE, 0.1, 40.0, SCALES);
+    let mut fcwt = FastCwt::new(w, scales, true);
+    let input = fcwt::util::chirp(SAMPLE_RATE as f32, SAMPLES, 0.1, 20.0);
+    c.bench_function("fcwt 2048x10 norm reuse", |b| b.iter(|| fcwt.cwt(&input)));
+
+    // Transforming into a preallocated result does not allocate
+    let mut output = CwtResult::new(SCALES, SAMPLES);
+    c.bench_function("fcwt 2048x10 norm into", |b| {
+        b.iter(|| fcwt.cwt_into(&input, &mut output))
+    });
 }
 
 criterion_group!(benches, criterion_benchmark);
@@ -93,7 +93,7 @@ impl Default for WaveletDemo {
 /*
 fn save_csv(filename: String, data: &CwtResult<f32>) {
     let mut writer = Writer::from_path(filename).unwrap();
-    for row in data.rows().iter() {
+    for row in data.rows() {
         let r: Vec<f32> = row.iter().map(|x| x.norm()).collect();
         writer.serialize(r).unwrap();
     }
@@ -113,7 +113,7 @@ impl eframe::App for WaveletDemo {
 
         // puffin_egui::profiler_window(ctx);
 
-        if self.texture.is_none() & !self.image.is_none() {
+        if self.texture.is_none() & self.image.is_some() {
             // Allocate a new texture
             if let Some(image) = &self.image {
                 let texture = ctx.load_texture("cwt", image.clone(), Default::default());
@@ -211,18 +211,14 @@ impl WaveletDemo {
             if self.image.is_none() || self.image.as_ref().unwrap().height() != output.num_scales()
             {
                 self.image = Some(egui::ColorImage::new(
-                    [output[0].len(), output.num_scales()],
+                    [output.num_samples(), output.num_scales()],
                     Color32::LIGHT_YELLOW,
                 ));
             }
 
-            for y in 0..output.num_scales() {
-                for x in 0..output[0].len() {
-                    let val = output[y][x];
-                    let c = Hsva::new(val.norm(), 1.0, 1.0, 1.0);
-                    if let Some(image) = &mut self.image {
-                        image.pixels[y * output[0].len() + x] = c.into();
-                    }
+            if let Some(image) = &mut self.image {
+                for (pixel, val) in image.pixels.iter_mut().zip(output.as_slice()) {
+                    *pixel = Hsva::new(val.norm(), 1.0, 1.0, 1.0).into();
                 }
             }
 
@@ -10,10 +10,10 @@ fn main() {
     let scales = LinFreqs::new(1000, 0.1, 40.0, 300);
     let mut fcwt = FastCwt::new(w, scales, true);
 
-    let mut input = fcwt::util::chirp(1000.0, 65536, 0.1, 20.0);
+    let input = fcwt::util::chirp(1000.0, 65536, 0.1, 20.0);
 
     for i in 0..100 {
-        fcwt.cwt(&mut input);
+        fcwt.cwt(&input);
         println!("Iter {}", i);
     }
 }
@@ -1,25 +1,181 @@
-use crate::fft::FftBackend;
-use crate::CwtResult;
+use std::cmp::Ordering;
+use std::ops::Range;
+use std::sync::{Arc, Mutex};
+
+use rayon::prelude::*;
+use rayon::ThreadPool;
+
+use rustfft::num_traits::Zero;
+
+use crate::fft::{default_planner, FftBackend, FftError, FftPlanner};
+use crate::padding::{padded_len, PaddingMode};
+use crate::{cast, Complex, CwtResult, Float};
 use crate::{scales::Scales, wavelet::Wavelet};
 
-type Float = super::Float;
-type Complex = super::Complex;
+/// Inverse FFT plan and scratch buffer owned by a single thread
+struct Worker<T: Float> {
+    fft: Box<dyn FftBackend<T> + Send>,
+    buffer: Vec<Complex<T>>,
+}
+
+impl<T: Float> Worker<T> {
+    fn new(planner: &dyn FftPlanner<T>, size: usize) -> Result<Self, FftError> {
+        Ok(Self {
+            fft: planner.plan(size)?,
+            buffer: vec![Complex::zero(); size],
+        })
+    }
+}
 
-pub struct FastCwt<W: Wavelet, S: Scales> {
+/// FFT plans and scratch buffers for a single transform size, reused across calls
+/// to [`FastCwt::cwt`] as long as the padded input length and thread count do not change.
+///
+/// Each worker is only ever locked by the rayon thread with the matching index,
+/// so the mutexes are uncontended.
+struct Workspace<T: Float> {
+    size: usize,
+    padded: Vec<T>,
+    /// Non-negative frequency bins of the padded input. The input is real,
+    /// so the negative frequencies are their conjugates.
+    spectrum: Vec<Complex<T>>,
+    workers: Vec<Mutex<Worker<T>>>,
+}
+
+impl<T: Float> Workspace<T> {
+    fn new(planner: &dyn FftPlanner<T>, size: usize, threads: usize) -> Result<Self, FftError> {
+        #[cfg(feature = "profile")]
+        puffin::profile_function!();
+
+        Ok(Self {
+            size,
+            padded: vec![T::zero(); size],
+            spectrum: vec![Complex::zero(); size / 2 + 1],
+            workers: (0..threads.max(1))
+                .map(|_| Worker::new(planner, size).map(Mutex::new))
+                .collect::<Result<_, _>>()?,
+        })
+    }
+}
+
+/// How the daughter wavelet's frequency response is sampled from the mother wavelet at
+/// each scale, trading accuracy for speed.
+///
+/// Bin `i` of the spectrum falls on the fractional mother index `scale * i / 2`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SamplingMode {
+    /// Truncate to the mother sample below, as fCWT does. Fastest, but large scales
+    /// reuse each sample across several bins and small scales skip samples.
+    #[default]
+    Truncate,
+    /// Interpolate linearly between the two neighbouring mother samples
+    Linear,
+    /// Evaluate [`Wavelet::frequency_response`] at every bin. Most accurate and slowest,
+    /// and falls back to [`SamplingMode::Linear`] for wavelets without a closed form response.
+    Analytic,
+}
+
+/// How each scale is convolved with the input.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ConvolutionMode {
+    /// Multiply with the daughter wavelet's frequency response, after padding the input
+    /// to a power of two
+    #[default]
+    Fft,
+    /// Correlate with the time-domain kernel from [`Wavelet::generate`], truncated at
+    /// [`Wavelet::get_support`]. The input is extended with the [`PaddingMode`] as far as
+    /// the kernel reaches, without padding to a power of two.
+    Direct,
+    /// Pick direct or FFT convolution per scale, whichever has the lower estimated cost.
+    /// Small scales with short kernels are convolved directly.
+    Auto,
+}
+
+pub struct FastCwt<W: Wavelet<T>, S: Scales<T>, T: Float = f32> {
     wavelet: W,
     scales: S,
     normalize: bool,
+    padding: PaddingMode,
+    sampling: SamplingMode,
+    convolution: ConvolutionMode,
+    parallel: bool,
+    pool: Option<Arc<ThreadPool>>,
+    planner: Arc<dyn FftPlanner<T>>,
+    workspace: Option<Workspace<T>>,
+    mother_size: usize,
+    kernels: Vec<Vec<Complex<T>>>,
 }
 
-impl<W: Wavelet, S: Scales> FastCwt<W, S> {
-    pub fn new(wavelet: W, scales: S, normalize: bool) -> Self {
+impl<W: Wavelet<T> + Sync, S: Scales<T> + Sync, T: Float> FastCwt<W, S, T> {
+    pub fn new(mut wavelet: W, scales: S, normalize: bool) -> Self {
+        let planner = default_planner();
+        wavelet.set_planner(planner.clone());
+
         Self {
             wavelet,
             scales,
             normalize,
+            padding: PaddingMode::default(),
+            sampling: SamplingMode::default(),
+            convolution: ConvolutionMode::default(),
+            parallel: false,
+            pool: None,
+            planner,
+            workspace: None,
+            mother_size: 0,
+            kernels: vec![],
         }
     }
 
+    /// Set how inputs are extended up to the next power of two before transforming
+    pub fn with_padding(mut self, padding: PaddingMode) -> Self {
+        self.padding = padding;
+        self
+    }
+
+    /// Set how the daughter wavelets are sampled from the mother wavelet
+    pub fn with_sampling(mut self, sampling: SamplingMode) -> Self {
+        self.sampling = sampling;
+        self
+    }
+
+    /// Set whether scales are convolved in the frequency or time domain
+    pub fn with_convolution(mut self, convolution: ConvolutionMode) -> Self {
+        self.convolution = convolution;
+        self
+    }
+
+    /// Plan FFTs with the given planner instead of the backend selected by the crate features,
+    /// for example [`RustFftPlanner`](crate::fft::RustFftPlanner) or a closure returning a
+    /// custom [`FftBackend`]
+    pub fn with_planner(mut self, planner: impl FftPlanner<T> + 'static) -> Self {
+        self.planner = Arc::new(planner);
+        self.wavelet.set_planner(self.planner.clone());
+        self.workspace = None;
+        self
+    }
+
+    /// Convolve scales in parallel on the global rayon thread pool
+    pub fn with_parallel(mut self, parallel: bool) -> Self {
+        self.parallel = parallel;
+        self
+    }
+
+    /// Convolve scales in parallel on a dedicated thread pool with the given number of threads
+    pub fn with_threads(self, threads: usize) -> Self {
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(threads)
+            .build()
+            .expect("failed to build thread pool");
+        self.with_thread_pool(Arc::new(pool))
+    }
+
+    /// Convolve scales in parallel on a caller provided thread pool
+    pub fn with_thread_pool(mut self, pool: Arc<ThreadPool>) -> Self {
+        self.parallel = true;
+        self.pool = Some(pool);
+        self
+    }
+
     #[inline(always)]
     pub fn wavelet(&self) -> &W {
         &self.wavelet
@@ -30,133 +186,617 @@ impl<W: Wavelet, S: Scales> FastCwt<W, S> {
         &self.scales
     }
 
-    pub fn cwt(&mut self, input: &[Float]) -> CwtResult<Float> {
-        #[cfg(feature = "profile")]
-        puffin::profile_function!();
+    #[inline(always)]
+    pub fn padding(&self) -> PaddingMode {
+        self.padding
+    }
 
-        #[cfg(feature = "profile")]
-        puffin::profile_scope!("cwt");
+    #[inline(always)]
+    pub fn sampling(&self) -> SamplingMode {
+        self.sampling
+    }
 
-        let mut fft = {
-            #[cfg(feature = "profile")]
-            puffin::profile_scope!("plan");
+    #[inline(always)]
+    pub fn convolution(&self) -> ConvolutionMode {
+        self.convolution
+    }
 
-            #[cfg(feature = "fftw")]
-            let fft = crate::fft::FftwBackend::<Float>::new(input.len());
+    #[inline(always)]
+    pub fn is_parallel(&self) -> bool {
+        self.parallel
+    }
 
-            #[cfg(not(feature = "fftw"))]
-            let fft = crate::fft::RustFftBackend::<Float>::new(input.len());
+    /// Number of threads the scales are convolved on
+    pub fn num_threads(&self) -> usize {
+        match (&self.pool, self.parallel) {
+            (Some(pool), true) => pool.current_num_threads(),
+            (None, true) => rayon::current_num_threads(),
+            (_, false) => 1,
+        }
+    }
 
-            fft
-        };
+    /// Cone of influence, the number of samples at either end of each row where edge effects
+    /// dominate. This is the e-folding time of the daughter wavelet at each scale.
+    pub fn cone_of_influence(&self) -> Vec<T> {
+        let efolding = self.wavelet.efolding_time();
+        (0..self.scales.len())
+            .map(|i| efolding * self.scales.scale(i))
+            .collect()
+    }
 
-        assert!(input.len().is_power_of_two());
+    /// Take the cached workspace for the given input size, planning a new one if the size
+    /// or number of threads changed
+    fn take_workspace(&mut self, size: usize) -> Result<Workspace<T>, FftError> {
+        let threads = self.num_threads();
+        match self.workspace.take() {
+            Some(workspace) if workspace.size == size && workspace.workers.len() == threads => {
+                Ok(workspace)
+            }
+            _ => Workspace::new(self.planner.as_ref(), size, threads),
+        }
+    }
 
+    /// Transform `input`, returning one row per scale with the same length as the input.
+    ///
+    /// For FFT convolution, inputs of any length are padded up to the next power of two
+    /// using the configured [`PaddingMode`], and the result is cropped back to the original
+    /// length.
+    ///
+    /// # Panics
+    ///
+    /// If the FFT backend fails to plan or execute a transform, see [`FastCwt::try_cwt`]
+    pub fn cwt(&mut self, input: &[T]) -> CwtResult<T> {
+        self.try_cwt(input)
+            .unwrap_or_else(|err| panic!("FFT failed: {err}"))
+    }
+
+    /// Transform `input` like [`FastCwt::cwt`], returning an error if the FFT backend
+    /// fails to plan or execute a transform
+    pub fn try_cwt(&mut self, input: &[T]) -> Result<CwtResult<T>, FftError> {
         let mut output = {
             #[cfg(feature = "profile")]
             puffin::profile_scope!("alloc");
             CwtResult::new(self.scales.len(), input.len())
         };
 
-        let mut buffer = {
+        self.try_cwt_into(input, &mut output)?;
+
+        Ok(output)
+    }
+
+    /// Transform `input` into a caller provided result, which is resized to one row per
+    /// scale with the same length as the input.
+    ///
+    /// Once the result and the cached workspace have the right size, repeated transforms
+    /// do not allocate.
+    ///
+    /// # Panics
+    ///
+    /// If the FFT backend fails to plan or execute a transform, see [`FastCwt::try_cwt_into`]
+    pub fn cwt_into(&mut self, input: &[T], output: &mut CwtResult<T>) {
+        self.try_cwt_into(input, output)
+            .unwrap_or_else(|err| panic!("FFT failed: {err}"))
+    }
+
+    /// Transform `input` into a caller provided result like [`FastCwt::cwt_into`], returning
+    /// an error if the FFT backend fails to plan or execute a transform. The rows are
+    /// unspecified after an error.
+    pub fn try_cwt_into(&mut self, input: &[T], output: &mut CwtResult<T>) -> Result<(), FftError> {
+        #[cfg(feature = "profile")]
+        puffin::profile_function!();
+
+        let size = padded_len(input.len());
+
+        output.resize(self.scales.len(), input.len());
+
+        {
             #[cfg(feature = "profile")]
-            puffin::profile_scope!("alloc_input");
-            vec![Complex::new(0.0, 0.0); input.len()]
+            puffin::profile_scope!("kernels");
+            self.prepare_kernels(input.len());
+        }
+
+        // The input is only padded and transformed if any scale is convolved by FFT
+        let fft = (0..self.scales.len()).any(|i| !self.is_direct(i, input.len()));
+        let mut crop = 0..input.len();
+        let workspace = if fft {
+            let mut workspace = {
+                #[cfg(feature = "profile")]
+                puffin::profile_scope!("plan");
+                self.take_workspace(size)?
+            };
+
+            let offset = {
+                #[cfg(feature = "profile")]
+                puffin::profile_scope!("pad");
+                self.padding.pad(input, &mut workspace.padded)
+            };
+            crop = offset..offset + input.len();
+
+            {
+                #[cfg(feature = "profile")]
+                puffin::profile_scope!("fft");
+                let Workspace {
+                    padded,
+                    spectrum,
+                    workers,
+                    ..
+                } = &mut workspace;
+                workers[0]
+                    .lock()
+                    .unwrap()
+                    .fft
+                    .forward_into(padded, spectrum)?;
+            }
+
+            {
+                #[cfg(feature = "profile")]
+                puffin::profile_scope!("mother");
+                self.prepare_mother(size);
+            }
+
+            Some(workspace)
+        } else {
+            None
         };
 
-        let input_fft = {
+        let status = if self.parallel {
             #[cfg(feature = "profile")]
-            puffin::profile_scope!("fft");
-            fft.forward(input)
+            puffin::profile_scope!("scales_parallel");
+
+            let workspace = workspace.as_ref();
+            match &self.pool {
+                Some(pool) => {
+                    pool.install(|| self.convolve_parallel(workspace, input, &crop, output))
+                }
+                None => self.convolve_parallel(workspace, input, &crop, output),
+            }
+        } else {
+            let mut worker = workspace
+                .as_ref()
+                .map(|workspace| workspace.workers[0].lock().unwrap());
+
+            output.rows_mut().enumerate().try_for_each(|(i, row)| {
+                #[cfg(feature = "profile")]
+                puffin::profile_scope!("scale", i.to_string());
+
+                match (&mut worker, &workspace) {
+                    (Some(worker), Some(workspace)) if !self.is_direct(i, input.len()) => self
+                        .convolve(
+                            worker,
+                            &workspace.spectrum,
+                            self.scales.scale(i),
+                            &crop,
+                            row,
+                        ),
+                    _ => {
+                        self.convolve_direct(input, &self.kernels[i], row);
+                        Ok(())
+                    }
+                }
+            })
         };
 
+        if workspace.is_some() {
+            self.workspace = workspace;
+        }
+
+        #[cfg(feature = "profile")]
+        puffin::GlobalProfiler::lock().new_frame();
+
+        status
+    }
+
+    /// Reconstruct a signal from all scales of a transform computed by this instance.
+    ///
+    /// See [`FastCwt::icwt_scales`].
+    pub fn icwt(&mut self, result: &CwtResult<T>) -> Vec<T> {
+        self.icwt_scales(result, 0..self.scales.len())
+    }
+
+    /// Reconstruct a signal from all scales like [`FastCwt::icwt`], returning an error if
+    /// the FFT backend fails
+    pub fn try_icwt(&mut self, result: &CwtResult<T>) -> Result<Vec<T>, FftError> {
+        self.try_icwt_scales(result, 0..self.scales.len())
+    }
+
+    /// Reconstruct a signal from a subset of the scales of a transform computed by this instance.
+    ///
+    /// Uses the single integral (Morlet) inverse, summing each scale weighted by its spacing
+    /// in log scale and dividing by the wavelet's
+    /// [reconstruction constant](Wavelet::reconstruction_constant). For analytic wavelets
+    /// the rows hold the positive frequency half of the signal, so the real part is doubled,
+    /// or the imaginary part for imaginary frequency responses. Real wavelets with an
+    /// imaginary response, like odd derivatives of Gaussians, recover the Hilbert transform
+    /// of the signal, which is undone with an FFT of the padded sum.
+    ///
+    /// The mean of the signal is not recovered, and accuracy depends on the scales covering
+    /// the signal's bandwidth densely enough for the wavelet's frequency resolution.
+    ///
+    /// # Panics
+    ///
+    /// If the FFT backend fails to plan or execute the inverse Hilbert transform, see
+    /// [`FastCwt::try_icwt_scales`]
+    pub fn icwt_scales<I: IntoIterator<Item = usize>>(
+        &mut self,
+        result: &CwtResult<T>,
+        scales: I,
+    ) -> Vec<T> {
+        self.try_icwt_scales(result, scales)
+            .unwrap_or_else(|err| panic!("FFT failed: {err}"))
+    }
+
+    /// Reconstruct a signal from a subset of scales like [`FastCwt::icwt_scales`], returning
+    /// an error if the FFT backend fails
+    pub fn try_icwt_scales<I: IntoIterator<Item = usize>>(
+        &mut self,
+        result: &CwtResult<T>,
+        scales: I,
+    ) -> Result<Vec<T>, FftError> {
+        assert_eq!(
+            result.num_scales(),
+            self.scales.len(),
+            "result must have been transformed with the same scales"
+        );
+
+        let size = padded_len(result.num_samples());
+        self.prepare_mother(size);
+
+        let imaginary = self.wavelet.imag_frequency();
+        let double_sided = self.wavelet.is_double_sided();
+
+        // Rows are scaled by the FFT length unless the transform was normalized
+        let sides: T = if double_sided { T::one() } else { cast(2.0) };
+        let mut norm = sides / self.wavelet.reconstruction_constant();
+        if !self.normalize {
+            norm = norm / cast(size as f64);
+        }
+
+        let mut output = vec![T::zero(); result.num_samples()];
+
+        for i in scales {
+            let weight = self.scale_weight(i) * norm;
+            for (out, value) in output.iter_mut().zip(result.row(i)) {
+                // The conjugate of an imaginary response rotated the analytic half by -i
+                let value = if imaginary && !double_sided {
+                    -value.im
+                } else {
+                    value.re
+                };
+                *out = *out + value * weight;
+            }
+        }
+
+        if imaginary && double_sided {
+            self.inverse_hilbert(&mut output)?;
+        }
+
+        Ok(output)
+    }
+
+    /// Invert the Hilbert transform of `signal` in place, multiplying positive
+    /// frequencies by `i` and negative frequencies by `-i`
+    fn inverse_hilbert(&mut self, signal: &mut [T]) -> Result<(), FftError> {
+        let size = padded_len(signal.len());
+        let mut workspace = self.take_workspace(size)?;
+
+        let offset = self.padding.pad(signal, &mut workspace.padded);
+
         {
-            #[cfg(feature = "profile")]
-            puffin::profile_scope!("mother");
-            self.wavelet.generate_mother(input.len());
+            let Workspace {
+                padded,
+                spectrum,
+                workers,
+                ..
+            } = &mut workspace;
+            let mut worker = workers[0].lock().unwrap();
+            let Worker { fft, buffer } = &mut *worker;
+            fft.forward_into(padded, spectrum)?;
+
+            // Negative frequency bins are the conjugates of the positive ones
+            for (k, bin) in buffer.iter_mut().enumerate() {
+                *bin = match (2 * k).cmp(&size) {
+                    Ordering::Less if k > 0 => Complex::new(-spectrum[k].im, spectrum[k].re),
+                    Ordering::Greater => {
+                        let bin = spectrum[size - k];
+                        Complex::new(-bin.im, -bin.re)
+                    }
+                    _ => Complex::zero(),
+                };
+            }
+
+            fft.inverse_in_place(buffer)?;
+
+            let scale: T = cast(size as f64);
+            for (out, value) in signal.iter_mut().zip(&buffer[offset..]) {
+                *out = value.re / scale;
+            }
         }
 
-        for i in 0..self.scales.len() {
+        self.workspace = Some(workspace);
+        Ok(())
+    }
+
+    /// Generate the mother wavelet for a padded size. It only depends on the size,
+    /// so it is only regenerated when that changes.
+    fn prepare_mother(&mut self, size: usize) {
+        if self.mother_size != size || self.wavelet.mother().is_empty() {
+            self.wavelet.generate_mother(size);
+            self.mother_size = size;
+        }
+    }
+
+    /// Trapezoidal integration weight of a scale in log scale
+    fn scale_weight(&self, index: usize) -> T {
+        let len = self.scales.len();
+        if len < 2 {
+            return T::one();
+        }
+
+        let lower = self.scales.scale(index.saturating_sub(1)).ln();
+        let upper = self.scales.scale((index + 1).min(len - 1)).ln();
+
+        (upper - lower).abs() / cast(2.0)
+    }
+
+    /// Convolve all scales on the current rayon thread pool, each thread using its own worker
+    /// for the scales convolved by FFT
+    fn convolve_parallel(
+        &self,
+        workspace: Option<&Workspace<T>>,
+        input: &[T],
+        crop: &Range<usize>,
+        output: &mut CwtResult<T>,
+    ) -> Result<(), FftError> {
+        output.par_rows_mut().enumerate().try_for_each(|(i, row)| {
             #[cfg(feature = "profile")]
             puffin::profile_scope!("scale", i.to_string());
 
-            let row = self.convolve(
-                &mut fft,
-                &input_fft.to_vec(),
-                &mut buffer,
-                self.scales.scale(i),
-            );
+            match workspace {
+                Some(workspace) if !self.is_direct(i, input.len()) => {
+                    let index =
+                        rayon::current_thread_index().unwrap_or(0) % workspace.workers.len();
+                    let mut worker = workspace.workers[index].lock().unwrap();
+                    self.convolve(
+                        &mut worker,
+                        &workspace.spectrum,
+                        self.scales.scale(i),
+                        crop,
+                        row,
+                    )
+                }
+                _ => {
+                    self.convolve_direct(input, &self.kernels[i], row);
+                    Ok(())
+                }
+            }
+        })
+    }
 
-            {
-                #[cfg(feature = "profile")]
-                puffin::profile_scope!("push", i.to_string());
-                output.push_row(row);
+    /// Whether the scale at `index` is convolved directly for an input of `len` samples.
+    ///
+    /// [`ConvolutionMode::Auto`] compares the multiply-adds of correlating with the kernel
+    /// against an inverse FFT of the padded size, about `5 n log2(n)` operations, plus the
+    /// multiplication with the daughter wavelet.
+    fn is_direct(&self, index: usize, len: usize) -> bool {
+        match self.convolution {
+            ConvolutionMode::Fft => false,
+            ConvolutionMode::Direct => true,
+            ConvolutionMode::Auto => {
+                let taps = 2 * self.wavelet.get_support(self.scales.scale(index)).max(0) + 1;
+                let size = padded_len(len);
+
+                let direct = 4 * len * taps as usize;
+                let fft = size * (5 * size.trailing_zeros() as usize + 3);
+                direct < fft
             }
         }
+    }
 
-        if self.normalize {
-            #[cfg(feature = "profile")]
-            puffin::profile_scope!("normalize");
-            output.normalize();
+    /// Generate the time-domain kernels of the scales convolved directly, if not already
+    /// cached. Kernels are normalized like the FFT path, independent of the input length.
+    fn prepare_kernels(&mut self, len: usize) {
+        if self.kernels.len() != self.scales.len() {
+            self.kernels = vec![vec![]; self.scales.len()];
         }
 
+        for i in 0..self.scales.len() {
+            if self.kernels[i].is_empty() && self.is_direct(i, len) {
+                self.kernels[i] = self.wavelet.generate(1, self.scales.scale(i));
+            }
+        }
+    }
+
+    /// Correlate `input` with `kernel`, extending the input with the configured
+    /// [`PaddingMode`] where the kernel reaches past either end
+    fn convolve_direct(&self, input: &[T], kernel: &[Complex<T>], row: &mut [Complex<T>]) {
         #[cfg(feature = "profile")]
-        puffin::GlobalProfiler::lock().new_frame();
+        puffin::profile_function!();
+
+        let width = kernel.len() / 2;
+
+        // Unnormalized results match the FFT path, scaled by the padded length
+        let norm: T = if self.normalize {
+            T::one()
+        } else {
+            cast(padded_len(input.len()) as f64)
+        };
+
+        for (b, value) in row.iter_mut().enumerate() {
+            let sum = if b >= width && b + kernel.len() - width <= input.len() {
+                kernel
+                    .iter()
+                    .zip(&input[b - width..])
+                    .fold(Complex::zero(), |acc, (k, &x)| acc + k.conj().scale(x))
+            } else {
+                kernel
+                    .iter()
+                    .enumerate()
+                    .fold(Complex::zero(), |acc, (k, w)| {
+                        let t = (b + k) as isize - width as isize;
+                        match self.padding.source_index(t, input.len()) {
+                            Some(j) => acc + w.conj().scale(input[j]),
+                            None => acc,
+                        }
+                    })
+            };
 
-        output
+            *value = sum.scale(norm);
+        }
     }
 
+    /// Convolve the input spectrum with the daughter wavelet at `scale`, writing the
+    /// `crop` range of the padded result into `row`
     fn convolve(
-        &mut self,
-        fft: &mut dyn FftBackend<Float>,
-        input: &Vec<Complex>,
-        buffer: &mut Vec<Complex>,
-        scale: Float,
-    ) -> Vec<Complex> {
+        &self,
+        worker: &mut Worker<T>,
+        input: &[Complex<T>],
+        scale: T,
+        crop: &Range<usize>,
+        row: &mut [Complex<T>],
+    ) -> Result<(), FftError> {
         #[cfg(feature = "profile")]
         puffin::profile_function!();
 
-        self.daughter_wavelet_multiply(input, buffer, scale, false, false);
+        self.daughter_wavelet_multiply(
+            input,
+            &mut worker.buffer,
+            scale,
+            self.wavelet.imag_frequency(),
+            self.wavelet.is_double_sided(),
+        );
+
+        // Normalize by the padded FFT length, as the cropped row is shorter
+        let size: T = cast(worker.buffer.len() as f64);
+
+        // Unpadded inputs are transformed straight into the row
+        if crop.len() == worker.buffer.len() {
+            worker.fft.inverse_into(&mut worker.buffer, row)?;
+            if self.normalize {
+                row.iter_mut().for_each(|v| *v = v.unscale(size));
+            }
+            return Ok(());
+        }
+
+        worker.fft.inverse_in_place(&mut worker.buffer)?;
 
-        let output = fft.inverse(buffer);
+        let result = &worker.buffer[crop.clone()];
 
-        output
+        if self.normalize {
+            row.iter_mut()
+                .zip(result)
+                .for_each(|(v, x)| *v = x.unscale(size));
+        } else {
+            row.copy_from_slice(result);
+        }
+        Ok(())
     }
 
+    /// Multiply the input spectrum with the conjugate of the daughter wavelet's frequency
+    /// response at `scale`, which correlates the input with the daughter wavelet.
+    ///
+    /// `input` holds the non-negative frequency bins of a real signal padded to
+    /// `output.len()`, with the negative frequencies following as their conjugates.
+    ///
+    /// The response is `mother` on positive frequencies, or `i * mother` if `imaginary`.
+    /// If `doublesided`, the wavelet is real valued and negative frequencies get the
+    /// conjugate of the response at the matching positive frequency.
     fn daughter_wavelet_multiply(
-        &mut self,
-        input: &Vec<Complex>,
-        output: &mut Vec<Complex>,
-        scale: f32,
+        &self,
+        input: &[Complex<T>],
+        output: &mut [Complex<T>],
+        scale: T,
         imaginary: bool,
         doublesided: bool,
     ) {
         #[cfg(feature = "profile")]
         puffin::profile_function!();
 
-        let size = input.len();
-        let step = scale / 2.0;
-        let endpoint = ((size as f32) / 2.0).min((size as f32) * 2.0 / scale) as usize;
-
+        let size = output.len();
         let mother = self.wavelet.mother();
-        assert!(mother.len() > 0);
+        assert!(!mother.is_empty());
+
+        // Mother sample j of a mother generated for `size` lies at bin 2j/scale,
+        // so the mother covers bins up to 2 * len / scale
+        let last: T = cast((mother.len() - 1) as f64);
+        let two: T = cast(2.0);
+        let step = scale / two;
+        let endpoint = cast::<T>((size / 2) as f64)
+            .min(cast::<T>(mother.len() as f64) * two / scale)
+            .to_usize()
+            .unwrap();
+
+        // Skip bins outside the frequency support of compactly supported wavelets
+        let (start, endpoint) = match self.wavelet.frequency_support() {
+            Some(range) => {
+                let bins = cast::<T>(size as f64) / scale;
+                let start = (range.start * bins).ceil().to_usize().unwrap_or(0);
+                let end = (range.end * bins).floor().to_usize().unwrap_or(0) + 1;
+                (start.min(endpoint), end.min(endpoint))
+            }
+            None => (0, endpoint),
+        };
+
+        // Wavelets without a closed form response are interpolated instead
+        let sampling = match self.sampling {
+            SamplingMode::Analytic if self.wavelet.frequency_response(T::one()).is_none() => {
+                SamplingMode::Linear
+            }
+            sampling => sampling,
+        };
+        let bin_width = two / cast(size as f64);
+
+        let sample = |i: usize| -> T {
+            let index = step * cast(i as f64);
+            match sampling {
+                SamplingMode::Truncate => mother[last.min(index).to_usize().unwrap()],
+                SamplingMode::Linear => {
+                    if index >= last {
+                        return mother[mother.len() - 1];
+                    }
+                    let lower = index.floor();
+                    let j = lower.to_usize().unwrap();
+                    mother[j] + (mother[j + 1] - mother[j]) * (index - lower)
+                }
+                SamplingMode::Analytic => self
+                    .wavelet
+                    .frequency_response(index * bin_width)
+                    .unwrap_or_else(T::zero),
+            }
+        };
+
+        output[..start].fill(Complex::zero());
 
-        for i in 0..endpoint {
-            let mother_index = ((size - 1) as f32).min(step * i as f32);
+        for i in start..endpoint {
+            let value = input[i] * sample(i);
 
-            output[i].re = input[i].re * mother[mother_index as usize];
-            output[i].im = input[i].im
-                * mother[mother_index as usize]
-                * (1.0 - 2.0 * (imaginary as i32 as f32));
+            // Multiply by -i for the conjugate of an imaginary response
+            output[i] = if imaginary {
+                Complex::new(value.im, -value.re)
+            } else {
+                value
+            };
         }
 
+        // Clear bins beyond the wavelet support, which may hold values from a previous scale
+        let tail = if doublesided {
+            (size - endpoint + 1).min(size)
+        } else {
+            size
+        };
+        output[endpoint..tail].fill(Complex::zero());
+
         if doublesided {
-            for i in 0..endpoint {
-                let mother_index = (size - 1).min((step * i as f32) as usize);
-                output[size - 1 - i].re = input[size - 1 - i].re
-                    * mother[mother_index]
-                    * (1.0 - 2.0 * (imaginary as i32 as f32));
-                output[size - 1 - i].im = input[size - 1 - i].im * mother[mother_index];
+            output[(size - start + 1).min(size)..].fill(Complex::zero());
+
+            // Bin size - i holds frequency -i, where the conjugate response is the response at i
+            for i in start.max(1)..endpoint {
+                let value = input[i].conj() * sample(i);
+
+                output[size - i] = if imaginary {
+                    Complex::new(-value.im, value.re)
+                } else {
+                    value
+                };
             }
         }
     }
@@ -165,8 +805,10 @@ impl<W: Wavelet, S: Scales> FastCwt<W, S> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::fft::PlanResult;
     use crate::scales::LinFreqs;
-    use crate::wavelet::MorletWavelet;
+    use crate::scales::{LogFreqs, LogScales};
+    use crate::wavelet::{BumpWavelet, CustomWavelet, DogWavelet, FbspWavelet, MorletWavelet};
 
     #[test]
     fn test_fast_cwt_new() {
@@ -174,7 +816,7 @@ mod tests {
         let s = LinFreqs::new(100, 10.0, 20.0, 5);
         let mut fast_cwt = FastCwt::new(w, s, false);
         // Check if the FastCwt instance is created successfully
-        assert_eq!(fast_cwt.cwt(&mut vec![0.0; 8]).rows().len(), 5);
+        assert_eq!(fast_cwt.cwt(&[0.0; 8]).rows().len(), 5);
     }
 
     #[test]
@@ -183,8 +825,8 @@ mod tests {
         let s = LinFreqs::new(100, 10.0, 20.0, 5);
         let slen = s.len();
         let mut fast_cwt = FastCwt::new(w, s, false);
-        let mut input = vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0];
-        let output = fast_cwt.cwt(&mut input);
+        let input = vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0];
+        let output = fast_cwt.cwt(&input);
 
         // Check if the output has the correct dimensions
         assert_eq!(output.rows().len(), slen);
@@ -192,13 +834,265 @@ mod tests {
     }
 
     #[test]
-    #[should_panic]
+    fn test_fast_cwt_workspace_reuse() {
+        let w = MorletWavelet::new(1.0);
+        let s = LinFreqs::new(100, 10.0, 20.0, 5);
+        let mut fast_cwt = FastCwt::new(w, s, true);
+        let input = crate::util::chirp(100.0, 64, 10.0, 20.0);
+
+        let first = fast_cwt.cwt(&input);
+        assert_eq!(fast_cwt.workspace.as_ref().map(|w| w.size), Some(64));
+
+        // A second transform of the same size reuses the plan and produces identical output
+        let second = fast_cwt.cwt(&input);
+        assert_eq!(first.as_slice(), second.as_slice());
+
+        // Changing the input size replans the workspace and regenerates the mother wavelet
+        let output = fast_cwt.cwt(&input[..32]);
+        assert_eq!(fast_cwt.workspace.as_ref().map(|w| w.size), Some(32));
+        assert_eq!(fast_cwt.wavelet().mother().len(), 32);
+        assert_eq!(output.num_samples(), 32);
+    }
+
+    #[test]
+    fn test_fast_cwt_parallel_matches_serial() {
+        let input = crate::util::chirp(1000.0, 1024, 1.0, 100.0);
+
+        let mut serial = FastCwt::new(
+            MorletWavelet::new(2.0),
+            LinFreqs::new(1000, 1.0, 200.0, 64),
+            true,
+        );
+        let mut parallel = FastCwt::new(
+            MorletWavelet::new(2.0),
+            LinFreqs::new(1000, 1.0, 200.0, 64),
+            true,
+        )
+        .with_threads(4);
+
+        assert!(parallel.is_parallel());
+        assert_eq!(parallel.num_threads(), 4);
+
+        let expected = serial.cwt(&input);
+
+        // Run twice to exercise the cached per-thread workers
+        for _ in 0..2 {
+            let output = parallel.cwt(&input);
+            assert_eq!(output.num_scales(), expected.num_scales());
+            assert_eq!(output.as_slice(), expected.as_slice());
+        }
+    }
+
+    #[test]
     fn test_fast_cwt_cwt_non_power_of_two() {
         let w = MorletWavelet::new(1.0);
         let s = LinFreqs::new(100, 10.0, 20.0, 5);
         let mut fast_cwt = FastCwt::new(w, s, false);
-        let mut input = vec![1.0, 2.0, 3.0, 4.0, 5.0]; // Not a power of two
-        let _ = fast_cwt.cwt(&mut input);
+        let input = vec![1.0, 2.0, 3.0, 4.0, 5.0]; // Not a power of two
+        let output = fast_cwt.cwt(&input);
+
+        // Padded internally to 8 samples and cropped back to the input length
+        assert_eq!(fast_cwt.wavelet().mother().len(), 8);
+        assert_eq!(output.num_samples(), input.len());
+        assert!(output.rows().all(|row| row.len() == input.len()));
+    }
+
+    #[test]
+    fn test_fast_cwt_padding_modes() {
+        let input = crate::util::chirp(1000.0, 1000, 1.0, 50.0);
+
+        let mut fast_cwt = FastCwt::new(
+            MorletWavelet::new(2.0),
+            LinFreqs::new(1000, 1.0, 100.0, 16),
+            true,
+        );
+
+        for mode in [
+            PaddingMode::Zero,
+            PaddingMode::Reflect,
+            PaddingMode::Symmetric,
+            PaddingMode::Periodic,
+            PaddingMode::Edge,
+        ] {
+            fast_cwt = fast_cwt.with_padding(mode);
+            let output = fast_cwt.cwt(&input);
+            assert_eq!(output.num_samples(), input.len());
+            assert_eq!(output.rows().len(), 16);
+        }
+
+        // Zero padding centers the input, so it matches transforming a manually centered signal
+        fast_cwt = fast_cwt.with_padding(PaddingMode::Zero);
+        let output = fast_cwt.cwt(&input);
+        let mut centered = vec![0.0; 1024];
+        centered[12..1012].copy_from_slice(&input);
+        let reference = fast_cwt.cwt(&centered);
+        for (row, expected) in output.rows().zip(reference.rows()) {
+            assert_eq!(row, &expected[12..1012]);
+        }
+    }
+
+    #[test]
+    fn test_fast_cwt_f64() {
+        let mut single = FastCwt::new(
+            MorletWavelet::<f32>::new(2.0),
+            LinFreqs::new(1000, 1.0, 100.0, 16),
+            true,
+        );
+        let mut double = FastCwt::new(
+            MorletWavelet::<f64>::new(2.0),
+            LinFreqs::new(1000, 1.0, 100.0, 16),
+            true,
+        );
+
+        let output = single.cwt(&crate::util::chirp(1000.0, 1000, 1.0, 50.0));
+        let expected = double.cwt(&crate::util::chirp(1000.0, 1000, 1.0, 50.0));
+
+        assert_eq!(output.num_scales(), expected.num_scales());
+        for (a, b) in output.as_slice().iter().zip(expected.as_slice()) {
+            assert!((a.re as f64 - b.re).abs() < 1e-3);
+            assert!((a.im as f64 - b.im).abs() < 1e-3);
+        }
+    }
+
+    /// Relative RMS error between `a` and `b` over the given range
+    fn rms_error(a: &[f64], b: &[f64], range: Range<usize>) -> f64 {
+        let error: f64 = a[range.clone()]
+            .iter()
+            .zip(&b[range.clone()])
+            .map(|(x, y)| (x - y).powi(2))
+            .sum();
+        let power: f64 = b[range].iter().map(|y| y.powi(2)).sum();
+        (error / power).sqrt()
+    }
+
+    #[test]
+    fn test_icwt_chirp_round_trip() {
+        let input = crate::util::chirp::<f64>(1000.0, 4096, 20.0, 60.0);
+
+        for normalize in [true, false] {
+            let mut fast_cwt = FastCwt::new(
+                MorletWavelet::new(1.0),
+                LinFreqs::new(1000, 5.0, 200.0, 400),
+                normalize,
+            )
+            .with_padding(PaddingMode::Symmetric);
+
+            let result = fast_cwt.cwt(&input);
+            let output = fast_cwt.icwt(&result);
+
+            assert_eq!(output.len(), input.len());
+            let error = rms_error(&output, &input, 256..3840);
+            assert!(error < 0.01, "reconstruction error {error}");
+        }
+    }
+
+    #[test]
+    fn test_icwt_scale_subset() {
+        // Two tones, 40Hz and 150Hz, at 1kHz sample rate
+        let low = crate::util::chirp::<f64>(1000.0, 4096, 40.0, 40.0);
+        let high = crate::util::chirp::<f64>(1000.0, 4096, 150.0, 150.0);
+        let input: Vec<f64> = low.iter().zip(&high).map(|(a, b)| a + b).collect();
+
+        let scales = LinFreqs::new(1000, 5.0, 250.0, 400);
+        let mut fast_cwt =
+            FastCwt::new(MorletWavelet::new(1.0), scales, true).with_padding(PaddingMode::Periodic);
+
+        let result = fast_cwt.cwt(&input);
+
+        // Keep only scales below 90Hz, removing the high tone
+        let band: Vec<usize> = (0..fast_cwt.scales().len())
+            .filter(|&i| fast_cwt.scales().freq(i) < 90.0)
+            .collect();
+        let output = fast_cwt.icwt_scales(&result, band);
+
+        let error = rms_error(&output, &low, 256..3840);
+        assert!(error < 0.01, "band reconstruction error {error}");
+    }
+
+    #[test]
+    fn test_fast_cwt_into() {
+        let input = crate::util::chirp(1000.0, 1000, 1.0, 50.0);
+        let mut fast_cwt = FastCwt::new(
+            MorletWavelet::new(2.0),
+            LinFreqs::new(1000, 1.0, 100.0, 16),
+            true,
+        );
+
+        let expected = fast_cwt.cwt(&input);
+
+        // A result of the wrong shape is resized to fit
+        let mut output = CwtResult::new(1, 1);
+        fast_cwt.cwt_into(&input, &mut output);
+        assert_eq!(output.num_scales(), 16);
+        assert_eq!(output.num_samples(), 1000);
+        assert_eq!(output.as_slice(), expected.as_slice());
+
+        // Overwriting an existing result replaces all of its contents
+        fast_cwt.cwt_into(&input[..500], &mut output);
+        assert_eq!(output.num_samples(), 500);
+        fast_cwt.cwt_into(&input, &mut output);
+        assert_eq!(output.as_slice(), expected.as_slice());
+    }
+
+    mod alloc {
+        use super::*;
+        use std::alloc::{GlobalAlloc, Layout, System};
+        use std::cell::Cell;
+
+        /// Allocator counting allocations made by the current thread
+        struct CountingAllocator;
+
+        thread_local! {
+            static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+        }
+
+        unsafe impl GlobalAlloc for CountingAllocator {
+            unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+                let _ = ALLOCATIONS.try_with(|count| count.set(count.get() + 1));
+                System.alloc(layout)
+            }
+
+            unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+                System.dealloc(ptr, layout)
+            }
+        }
+
+        #[global_allocator]
+        static GLOBAL: CountingAllocator = CountingAllocator;
+
+        fn allocations() -> usize {
+            ALLOCATIONS.with(|count| count.get())
+        }
+
+        #[test]
+        fn test_fast_cwt_into_does_not_allocate() {
+            let input = crate::util::chirp(1000.0, 1000, 1.0, 50.0);
+
+            for convolution in [ConvolutionMode::Fft, ConvolutionMode::Auto] {
+                let mut fast_cwt = FastCwt::new(
+                    MorletWavelet::new(2.0),
+                    LinFreqs::new(1000, 1.0, 100.0, 16),
+                    true,
+                )
+                .with_padding(crate::PaddingMode::Reflect)
+                .with_convolution(convolution);
+                let mut output = CwtResult::new(16, 1000);
+
+                // The first transform plans the workspace, generates the mother wavelet
+                // and the kernels of scales convolved directly
+                fast_cwt.cwt_into(&input, &mut output);
+
+                let before = allocations();
+                for _ in 0..4 {
+                    fast_cwt.cwt_into(&input, &mut output);
+                }
+                assert_eq!(allocations(), before, "{convolution:?}");
+
+                // Sanity check that allocations are counted
+                let _ = fast_cwt.cwt(&input);
+                assert!(allocations() > before);
+            }
+        }
     }
 
     #[test]
@@ -208,30 +1102,562 @@ mod tests {
         let mut fast_cwt = FastCwt::new(w, s, false);
         fast_cwt.wavelet.generate_mother(1024);
 
-        // Define the input data and parameters
+        // Define the input data and parameters, the non-negative bins of a 4 point spectrum
         let input = vec![
             Complex::new(1.0, 2.0),
             Complex::new(3.0, 4.0),
             Complex::new(5.0, 6.0),
-            Complex::new(7.0, 8.0),
         ];
         let scale = 2.0;
-        let imaginary = false;
-        let doublesided = false;
-
-        let _expected_output = vec![
-            Complex::new(0.0, 0.0), // Replace with the expected values
-            Complex::new(0.0, 0.0), // Replace with the expected values
-            Complex::new(0.0, 0.0), // Replace with the expected values
-            Complex::new(0.0, 0.0), // Replace with the expected values
+        let m = fast_cwt.wavelet.mother()[..2].to_vec();
+
+        // Bins 0 and 1 are positive frequencies within the support, bin 2 is the Nyquist
+        // bin and bin 3 holds frequency -1, the conjugate of bin 1
+        let i = Complex::new(0.0, 1.0);
+        let cases = [
+            (
+                false,
+                false,
+                [
+                    input[0] * m[0],
+                    input[1] * m[1],
+                    Complex::zero(),
+                    Complex::zero(),
+                ],
+            ),
+            (
+                true,
+                false,
+                [
+                    -i * input[0] * m[0],
+                    -i * input[1] * m[1],
+                    Complex::zero(),
+                    Complex::zero(),
+                ],
+            ),
+            (
+                false,
+                true,
+                [
+                    input[0] * m[0],
+                    input[1] * m[1],
+                    Complex::zero(),
+                    input[1].conj() * m[1],
+                ],
+            ),
+            (
+                true,
+                true,
+                [
+                    -i * input[0] * m[0],
+                    -i * input[1] * m[1],
+                    Complex::zero(),
+                    i * input[1].conj() * m[1],
+                ],
+            ),
         ];
 
-        let mut buffer = vec![Complex::new(0.0, 0.0); 1024];
+        for (imaginary, doublesided, expected_output) in cases {
+            // Stale values from a previous scale must be overwritten
+            let mut buffer = vec![Complex::new(9.0, 9.0); 4];
+
+            fast_cwt.daughter_wavelet_multiply(&input, &mut buffer, scale, imaginary, doublesided);
+
+            assert_eq!(
+                buffer, expected_output,
+                "imaginary {imaginary}, doublesided {doublesided}"
+            );
+        }
+    }
+
+    /// Correlate `input`, extended with zeros, with the time-domain daughter wavelet
+    fn direct_correlation<W: Wavelet<f64>>(
+        wavelet: &W,
+        input: &[f64],
+        scale: f64,
+    ) -> Vec<Complex<f64>> {
+        let size = padded_len(input.len());
+        let kernel = wavelet.generate(size, scale);
+        let width = (kernel.len() / 2) as isize;
+
+        (0..input.len() as isize)
+            .map(|b| {
+                kernel
+                    .iter()
+                    .enumerate()
+                    .filter_map(|(k, value)| {
+                        let t = usize::try_from(b + k as isize - width).ok()?;
+                        input.get(t).map(|x| value.conj() * *x)
+                    })
+                    .sum::<Complex<f64>>()
+                    / size as f64
+            })
+            .collect()
+    }
+
+    fn assert_matches_direct<W: Wavelet<f64> + Sync>(wavelet: W) {
+        let input: Vec<f64> = (0..1024)
+            .map(|i| {
+                let t = i as f64;
+                (t / 5.0).sin() + 0.5 * (t / 17.0).cos() + if i == 500 { 3.0 } else { 0.0 }
+            })
+            .collect();
+
+        let scales = LogScales::new(1000, 1000.0 / 48.0, 1000.0 / 6.0, 4);
+        let mut fast_cwt = FastCwt::new(wavelet, scales, true);
+        let result = fast_cwt.cwt(&input);
+
+        for (i, row) in result.rows().enumerate() {
+            let expected =
+                direct_correlation(fast_cwt.wavelet(), &input, fast_cwt.scales().scale(i));
+            let peak = expected.iter().fold(0.0f64, |acc, x| acc.max(x.norm()));
+
+            // The Morlet kernel is truncated at three standard deviations
+            for (value, expected) in row[128..896].iter().zip(&expected[128..896]) {
+                assert!((value - expected).norm() < 0.05 * peak, "scale {i}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_fast_cwt_matches_direct_correlation() {
+        // Analytic wavelets with real and imaginary frequency responses
+        assert_matches_direct(MorletWavelet::new(1.0));
+        assert_matches_direct(CustomWavelet::new(
+            |omega: f64| {
+                let value = (-(omega - std::f64::consts::TAU).powi(2) / 2.0).exp();
+                Complex::new(0.0, if omega >= 0.0 { value } else { 0.0 })
+            },
+            std::f64::consts::TAU,
+        ));
+
+        // Real wavelets with real and imaginary two sided frequency responses
+        assert_matches_direct(DogWavelet::new(2));
+        assert_matches_direct(DogWavelet::new(1));
+
+        // Compact frequency support with a slowly decaying kernel
+        assert_matches_direct(FbspWavelet::new(3, 1.5, 1.0));
+    }
+
+    #[test]
+    fn test_fast_cwt_real_wavelets_real_output() {
+        let input = crate::util::chirp::<f64>(1000.0, 1000, 5.0, 100.0);
+
+        for order in 1..5 {
+            let scales = LogFreqs::new(1000, 5.0, 200.0, 32);
+            let result = FastCwt::new(DogWavelet::new(order), scales, false).cwt(&input);
+
+            let peak = result
+                .as_slice()
+                .iter()
+                .fold(0.0f64, |acc, x| acc.max(x.re.abs()));
+            assert!(result.as_slice().iter().all(|x| x.im.abs() < 1e-9 * peak));
+        }
+    }
+
+    #[test]
+    fn test_icwt_two_sided_and_imaginary() {
+        let input = crate::util::chirp::<f64>(1000.0, 4096, 20.0, 60.0);
+        let scales = LogFreqs::new(1000, 1.0, 500.0, 400);
+
+        let imaginary_morlet = CustomWavelet::new(
+            |omega: f64| {
+                let value = (-(omega - std::f64::consts::TAU).powi(2) / 2.0).exp();
+                Complex::new(0.0, if omega >= 0.0 { value } else { 0.0 })
+            },
+            std::f64::consts::TAU,
+        );
+        let mut fast_cwt = FastCwt::new(imaginary_morlet, scales.clone(), false)
+            .with_padding(PaddingMode::Symmetric);
+        let result = fast_cwt.cwt(&input);
+        let output = fast_cwt.icwt(&result);
+        let error = rms_error(&output, &input, 256..3840);
+        assert!(error < 0.01, "imaginary reconstruction error {error}");
+
+        // Derivatives of Gaussians are broadband, and the lowest orders only decay slowly
+        // towards zero frequency, so scales up to Nyquist miss part of the response of
+        // a signal even well below it
+        let input = crate::util::chirp::<f64>(1000.0, 4096, 5.0, 20.0);
+        for order in [1, 2, 3] {
+            let mut fast_cwt = FastCwt::new(DogWavelet::new(order), scales.clone(), true)
+                .with_padding(PaddingMode::Symmetric);
+            let result = fast_cwt.cwt(&input);
+            let output = fast_cwt.icwt(&result);
+            let error = rms_error(&output, &input, 256..3840);
+            assert!(error < 0.05, "order {order} reconstruction error {error}");
+        }
+    }
+
+    #[test]
+    fn test_daughter_wavelet_multiply_frequency_support() {
+        let s = LinFreqs::new(100, 10.0, 20.0, 5);
+        let mut fast_cwt = FastCwt::new(BumpWavelet::default(), s, false);
+        fast_cwt.wavelet.generate_mother(1024);
+
+        let input = vec![Complex::new(1.0, 0.0); 513];
+        let mut buffer = vec![Complex::new(1.0, 1.0); 1024];
+        fast_cwt.daughter_wavelet_multiply(&input, &mut buffer, 8.0, false, false);
+
+        // Bin i lies at u = 8i/1024, and the default bump is non-zero for 0.88 < u < 1.12
+        for (i, value) in buffer.iter().enumerate() {
+            assert_eq!(value.re > 0.0, (113..=143).contains(&i), "bin {i}");
+            assert_eq!(value.im, 0.0);
+        }
+    }
+
+    // Bump wavelet that does not report its frequency support, so no bins are skipped
+    struct Unbounded(BumpWavelet<f64>);
+
+    impl Wavelet<f64> for Unbounded {
+        fn generate_mother(&mut self, size: usize) -> Vec<f64> {
+            self.0.generate_mother(size)
+        }
+        fn generate(&self, size: usize, scale: f64) -> Vec<Complex<f64>> {
+            self.0.generate(size, scale)
+        }
+        fn bandwidth(&self) -> f64 {
+            self.0.bandwidth()
+        }
+        fn four_wavelen(&self) -> f64 {
+            self.0.four_wavelen()
+        }
+        fn imag_frequency(&self) -> bool {
+            self.0.imag_frequency()
+        }
+        fn is_double_sided(&self) -> bool {
+            self.0.is_double_sided()
+        }
+        fn mother(&self) -> &[f64] {
+            self.0.mother()
+        }
+        fn get_support(&self, scale: f64) -> isize {
+            self.0.get_support(scale)
+        }
+        fn frequency_response(&self, u: f64) -> Option<f64> {
+            self.0.frequency_response(u)
+        }
+    }
+
+    #[test]
+    fn test_daughter_wavelet_multiply_skips_bins() {
+        let s = LinFreqs::new(100, 10.0, 20.0, 5);
+        let mut skipped = FastCwt::new(BumpWavelet::default(), s.clone(), false);
+        let mut full = FastCwt::new(Unbounded(BumpWavelet::default()), s, false);
+        skipped.wavelet.generate_mother(1024);
+        full.wavelet.generate_mother(1024);
+
+        let input: Vec<Complex<f64>> = (0..513)
+            .map(|i| Complex::new(1.0 + i as f64, -0.5 * i as f64))
+            .collect();
+
+        for scale in [2.5, 8.0, 13.7, 40.0] {
+            let range = skipped.wavelet.frequency_support().unwrap();
+            let mut a = vec![Complex::new(1.0, 1.0); 1024];
+            let mut b = vec![Complex::new(1.0, 1.0); 1024];
+            skipped.daughter_wavelet_multiply(&input, &mut a, scale, false, false);
+            full.daughter_wavelet_multiply(&input, &mut b, scale, false, false);
+
+            // Skipped bins are cleared, and match the transform without skipping up to
+            // interpolation between the last non-zero mother sample and the edge
+            for (i, (x, y)) in a.iter().zip(b.iter()).enumerate() {
+                let u = scale * i as f64 / 1024.0;
+                if !(range.start..=range.end).contains(&u) {
+                    assert_eq!(*x, Complex::zero(), "scale {scale} bin {i}");
+                }
+                assert!((x - y).norm() < 1e-12, "scale {scale} bin {i}");
+            }
+        }
+
+        let input = crate::util::chirp::<f64>(100.0, 1000, 5.0, 25.0);
+        let a = skipped.cwt(&input);
+        let b = full.cwt(&input);
+        for (x, y) in a.rows().flatten().zip(b.rows().flatten()) {
+            assert!((x - y).norm() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn test_daughter_wavelet_multiply_sampling() {
+        let size = 1024;
+        let input = vec![Complex::new(1.0, 0.0); size / 2 + 1];
+        let norm = std::f64::consts::TAU.sqrt() * crate::IPI4;
 
-        // Call the function
-        fast_cwt.daughter_wavelet_multiply(&input, &mut buffer, scale, imaginary, doublesided);
+        // Maximum error against the exact Morlet response at u = scale * i / size
+        let error = |sampling: SamplingMode, scale: f64| {
+            let s = LinFreqs::new(100, 10.0, 20.0, 5);
+            let mut fast_cwt =
+                FastCwt::new(MorletWavelet::new(1.0), s, false).with_sampling(sampling);
+            fast_cwt.wavelet.generate_mother(size);
 
-        // Assert that the output is as expected
-        //assert_eq!(output, expected_output);
+            let mut buffer = vec![Complex::zero(); size];
+            fast_cwt.daughter_wavelet_multiply(&input, &mut buffer, scale, false, false);
+
+            buffer[..size / 2]
+                .iter()
+                .enumerate()
+                .map(|(i, value)| {
+                    let u = scale * i as f64 / size as f64;
+                    let expected =
+                        norm * (-(std::f64::consts::TAU * (u - 1.0)).powi(2) / 2.0).exp();
+                    (value.re - expected).abs()
+                })
+                .fold(0.0f64, f64::max)
+        };
+
+        // Large scales stretch each mother sample across several bins, and fractional
+        // steps at small scales land between samples
+        for scale in [0.7, 3.3, 37.3] {
+            let truncate = error(SamplingMode::Truncate, scale);
+            let linear = error(SamplingMode::Linear, scale);
+            let analytic = error(SamplingMode::Analytic, scale);
+
+            // The mother only covers u < 2, so the analytic response is cut off beyond it
+            assert!(linear < 0.01 * truncate, "scale {scale}");
+            assert!(analytic < 1e-6, "scale {scale}");
+        }
+    }
+
+    #[test]
+    fn test_fast_cwt_sampling_modes_agree() {
+        let input = crate::util::chirp::<f64>(1000.0, 1000, 5.0, 100.0);
+        let scales = LogFreqs::new(1000, 5.0, 200.0, 32);
+
+        let mut fast_cwt = FastCwt::new(MorletWavelet::new(1.0), scales.clone(), true);
+        assert_eq!(fast_cwt.sampling(), SamplingMode::Truncate);
+        let truncated = fast_cwt.cwt(&input);
+
+        let mut fast_cwt = FastCwt::new(MorletWavelet::new(1.0), scales, true)
+            .with_sampling(SamplingMode::Analytic);
+        let analytic = fast_cwt.cwt(&input);
+
+        let peak = analytic
+            .as_slice()
+            .iter()
+            .fold(0.0f64, |acc, x| acc.max(x.norm()));
+        for (x, y) in truncated.as_slice().iter().zip(analytic.as_slice()) {
+            assert!((x - y).norm() < 0.05 * peak);
+        }
+    }
+
+    #[test]
+    fn test_cone_of_influence() {
+        let scales = LogScales::new(1000, 5.0, 200.0, 16);
+        let fast_cwt = FastCwt::new(MorletWavelet::<f64>::new(2.0), scales, true);
+        let coi = fast_cwt.cone_of_influence();
+
+        assert_eq!(coi.len(), 16);
+        for (i, &width) in coi.iter().enumerate() {
+            let expected = 2f64.sqrt() * 2.0 * fast_cwt.scales().scale(i);
+            assert!((width - expected).abs() < 1e-9);
+        }
+    }
+
+    /// Compare the direct and FFT paths over the given range of each row
+    fn assert_direct_matches_fft<W: Wavelet<f64> + Sync>(
+        wavelet: impl Fn() -> W,
+        input: &[f64],
+        padding: PaddingMode,
+        normalize: bool,
+        range: Range<usize>,
+    ) {
+        // Small scales are left out, where the daughter wavelet reaches past the Nyquist
+        // frequency and the sampled kernel aliases
+        let scales = LogScales::new(1000, 1000.0 / 48.0, 1000.0 / 6.0, 12);
+        let fft = FastCwt::new(wavelet(), scales.clone(), normalize)
+            .with_padding(padding)
+            .cwt(input);
+        let direct = FastCwt::new(wavelet(), scales, normalize)
+            .with_padding(padding)
+            .with_convolution(ConvolutionMode::Direct)
+            .cwt(input);
+
+        for (i, (a, b)) in fft.rows().zip(direct.rows()).enumerate() {
+            let peak = a.iter().fold(0.0f64, |acc, x| acc.max(x.norm()));
+            for (x, y) in a[range.clone()].iter().zip(&b[range.clone()]) {
+                assert!((x - y).norm() < 0.02 * peak, "scale {i}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_direct_convolution_matches_fft() {
+        // A power of two input is not padded, so the FFT path wraps around like periodic
+        // padding and the rows match up to the edges
+        let input = crate::util::chirp::<f64>(1000.0, 1024, 5.0, 200.0);
+        for normalize in [true, false] {
+            let range = 0..input.len();
+            assert_direct_matches_fft(
+                || MorletWavelet::new(1.0),
+                &input,
+                PaddingMode::Periodic,
+                normalize,
+                range.clone(),
+            );
+            assert_direct_matches_fft(
+                || DogWavelet::new(2),
+                &input,
+                PaddingMode::Periodic,
+                normalize,
+                range.clone(),
+            );
+            assert_direct_matches_fft(
+                || DogWavelet::new(1),
+                &input,
+                PaddingMode::Periodic,
+                normalize,
+                range,
+            );
+        }
+
+        // Other lengths are padded differently by the two paths, but match away from the edges
+        let input = crate::util::chirp::<f64>(1000.0, 700, 5.0, 200.0);
+        assert_direct_matches_fft(
+            || MorletWavelet::new(1.0),
+            &input,
+            PaddingMode::Zero,
+            true,
+            150..550,
+        );
+    }
+
+    #[test]
+    fn test_auto_convolution() {
+        let input = crate::util::chirp::<f64>(1000.0, 1000, 5.0, 250.0);
+        let scales = LogFreqs::new(1000, 5.0, 250.0, 32);
+
+        let mut fast_cwt = FastCwt::new(MorletWavelet::new(0.5), scales.clone(), true)
+            .with_convolution(ConvolutionMode::Auto);
+        assert_eq!(fast_cwt.convolution(), ConvolutionMode::Auto);
+
+        // Short kernels at high frequencies are convolved directly, long ones by FFT
+        let direct: Vec<bool> = (0..32).map(|i| fast_cwt.is_direct(i, 1000)).collect();
+        assert!(direct[0] && !direct[31]);
+
+        let auto = fast_cwt.cwt(&input);
+        let fft = FastCwt::new(MorletWavelet::new(0.5), scales.clone(), true).cwt(&input);
+        let parallel = FastCwt::new(MorletWavelet::new(0.5), scales, true)
+            .with_convolution(ConvolutionMode::Auto)
+            .with_threads(2)
+            .cwt(&input);
+
+        assert_eq!(auto.as_slice(), parallel.as_slice());
+        for (a, b) in fft.rows().zip(auto.rows()) {
+            let peak = a.iter().fold(0.0f64, |acc, x| acc.max(x.norm()));
+            for (x, y) in a[200..800].iter().zip(&b[200..800]) {
+                assert!((x - y).norm() < 0.02 * peak);
+            }
+        }
+    }
+
+    /// Naive DFT, to check that backends other than the built-in ones plug in
+    struct DftBackend {
+        size: usize,
+    }
+
+    impl DftBackend {
+        fn transform(&self, data: &[Complex<f64>], sign: f64) -> Vec<Complex<f64>> {
+            (0..self.size)
+                .map(|k| {
+                    data.iter()
+                        .enumerate()
+                        .map(|(n, x)| {
+                            let phase = (k * n % self.size) as f64 / self.size as f64;
+                            x * Complex::from_polar(1.0, sign * std::f64::consts::TAU * phase)
+                        })
+                        .sum()
+                })
+                .collect()
+        }
+    }
+
+    impl FftBackend<f64> for DftBackend {
+        fn forward_into(
+            &mut self,
+            input: &[f64],
+            output: &mut [Complex<f64>],
+        ) -> Result<(), FftError> {
+            let input: Vec<Complex<f64>> = input.iter().map(|&x| Complex::new(x, 0.0)).collect();
+            output.copy_from_slice(&self.transform(&input, -1.0)[..output.len()]);
+            Ok(())
+        }
+
+        fn inverse_in_place(&mut self, data: &mut [Complex<f64>]) -> Result<(), FftError> {
+            let output = self.transform(data, 1.0);
+            data.copy_from_slice(&output);
+            Ok(())
+        }
+
+        fn inverse_into(
+            &mut self,
+            input: &mut [Complex<f64>],
+            output: &mut [Complex<f64>],
+        ) -> Result<(), FftError> {
+            output.copy_from_slice(&self.transform(input, 1.0));
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_fast_cwt_with_planner() {
+        use crate::fft::RustFftPlanner;
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let input = crate::util::chirp::<f64>(1000.0, 200, 5.0, 100.0);
+        let scales = LogFreqs::new(1000, 5.0, 200.0, 16);
+        let reference = FastCwt::new(MorletWavelet::new(1.0), scales.clone(), true)
+            .with_planner(RustFftPlanner)
+            .cwt(&input);
+
+        let planned = Arc::new(AtomicUsize::new(0));
+        let counter = planned.clone();
+        let mut fast_cwt = FastCwt::new(MorletWavelet::new(1.0), scales.clone(), true)
+            .with_planner(move |size| -> PlanResult<f64> {
+                counter.fetch_add(1, Ordering::Relaxed);
+                Ok(Box::new(DftBackend { size }))
+            });
+
+        // One backend per worker, planned once for the padded size
+        let result = fast_cwt.cwt(&input);
+        let _ = fast_cwt.cwt(&input);
+        assert_eq!(planned.load(Ordering::Relaxed), 1);
+
+        for (x, y) in reference.as_slice().iter().zip(result.as_slice()) {
+            assert!((x - y).norm() < 1e-9);
+        }
+
+        #[cfg(feature = "fftw")]
+        {
+            let fftw = FastCwt::new(MorletWavelet::new(1.0), scales, true)
+                .with_planner(crate::fft::FftwPlanner::default())
+                .cwt(&input);
+            for (x, y) in reference.as_slice().iter().zip(fftw.as_slice()) {
+                assert!((x - y).norm() < 1e-9);
+            }
+        }
+    }
+
+    #[test]
+    fn test_fast_cwt_planner_error() {
+        let input = crate::util::chirp::<f64>(1000.0, 200, 5.0, 100.0);
+        let scales = LogFreqs::new(1000, 5.0, 200.0, 16);
+        let mut fast_cwt = FastCwt::new(MorletWavelet::new(1.0), scales, true)
+            .with_planner(|size| -> PlanResult<f64> { Err(format!("no plan for {size}").into()) });
+
+        let error = fast_cwt.try_cwt(&input).err().unwrap();
+        assert_eq!(error.to_string(), "no plan for 256");
+
+        // Direct convolution runs no FFTs
+        let mut fast_cwt = fast_cwt.with_convolution(ConvolutionMode::Direct);
+        assert!(fast_cwt.try_cwt(&input).is_ok());
+    }
+
+    #[test]
+    #[should_panic(expected = "no plan for 256")]
+    fn test_fast_cwt_planner_panic() {
+        let input = crate::util::chirp::<f64>(1000.0, 200, 5.0, 100.0);
+        let scales = LogFreqs::new(1000, 5.0, 200.0, 16);
+        FastCwt::new(MorletWavelet::new(1.0), scales, true)
+            .with_planner(|size| -> PlanResult<f64> { Err(format!("no plan for {size}").into()) })
+            .cwt(&input);
     }
 }
@@ -1,123 +1,588 @@
+use crate::Complex;
+use rustfft::num_traits::Zero;
+use rustfft::{Fft, FftNum};
 use std::sync::Arc;
-use rustfft::{Fft,FftNum};
-use crate::{Complex, Float};
 
+/// Error from planning or executing an FFT, as reported by the backend
+pub type FftError = Box<dyn std::error::Error + Send + Sync>;
+
+/// Boxed backend, or the error that kept it from being planned
+pub type PlanResult<T> = Result<Box<dyn FftBackend<T> + Send>, FftError>;
+
+/// FFT of a fixed size. Transforms write into caller provided buffers, so a planned
+/// backend never allocates.
+///
+/// Transforms return an error if the backend can't execute on the given buffers.
+/// The pure Rust backend never fails.
 pub trait FftBackend<T> {
-    fn forward(&mut self, input: &[T]) -> Vec<Complex>;
-    fn inverse(&mut self, input: &mut [Complex]) -> Vec<Complex>;
+    /// Forward transform of a real input into its `input.len() / 2 + 1` non-negative
+    /// frequency bins, without allocating. The negative frequencies are their conjugates.
+    fn forward_into(&mut self, input: &[T], output: &mut [Complex<T>]) -> Result<(), FftError>;
+
+    /// Inverse transform of `data` in place, without allocating
+    fn inverse_in_place(&mut self, data: &mut [Complex<T>]) -> Result<(), FftError>;
+
+    /// Inverse transform of `input` into `output`, without allocating. `input` may be
+    /// used as scratch space, and holds unspecified values afterwards.
+    fn inverse_into(
+        &mut self,
+        input: &mut [Complex<T>],
+        output: &mut [Complex<T>],
+    ) -> Result<(), FftError>;
+}
+
+/// Plans [`FftBackend`]s for a transform size, so the backend can be chosen at runtime.
+///
+/// A [`crate::FastCwt`] plans one backend per worker thread whenever the padded input
+/// length changes. Closures taking the size and returning a [`PlanResult`] are planners.
+pub trait FftPlanner<T>: Send + Sync {
+    fn plan(&self, size: usize) -> PlanResult<T>;
+}
+
+impl<T, F> FftPlanner<T> for F
+where
+    F: Fn(usize) -> PlanResult<T> + Send + Sync,
+{
+    fn plan(&self, size: usize) -> PlanResult<T> {
+        self(size)
+    }
+}
+
+/// Planner for the backend selected by the crate features
+pub(crate) fn default_planner<T: crate::Float>() -> Arc<dyn FftPlanner<T>> {
+    Arc::new(T::default_backend as fn(usize) -> PlanResult<T>)
 }
 
-/// RustFFT Backend
+/// Planner for [`RustFftBackend`]s
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RustFftPlanner;
+
+impl<T: FftNum> FftPlanner<T> for RustFftPlanner {
+    fn plan(&self, size: usize) -> PlanResult<T> {
+        Ok(Box::new(RustFftBackend::new(size)))
+    }
+}
+
+/// RustFFT Backend.
+///
+/// Real inputs of even length are packed into a complex signal of half the length, which
+/// is transformed and then split into the non-negative frequency bins of the real input.
 pub struct RustFftBackend<T: FftNum> {
-    forward_scratch_buffer: Vec<Complex>,
-    inverse_scratch_buffer: Vec<Complex>,
+    forward_scratch_buffer: Vec<Complex<T>>,
+    inverse_scratch_buffer: Vec<Complex<T>>,
     forward_plan: Arc<dyn Fft<T>>,
     inverse_plan: Arc<dyn Fft<T>>,
+
+    // Packed half length signal and the twiddles `e^(-2 pi i k / size)` splitting it
+    packed: Vec<Complex<T>>,
+    twiddles: Vec<Complex<T>>,
 }
 
-impl <T: FftNum> RustFftBackend<T> {
+impl<T: FftNum> RustFftBackend<T> {
     pub fn new(size: usize) -> Self {
         let mut planner = rustfft::FftPlanner::<T>::new();
 
-        let forward_plan = planner.plan_fft_forward(size);
+        // Odd lengths can't be packed, and are transformed as complex signals instead
+        let half = size / 2;
+        let packed = size.is_multiple_of(2) && half > 0;
+        let forward_plan = planner.plan_fft_forward(if packed { half } else { size });
         let inverse_plan = planner.plan_fft_inverse(size);
 
+        let twiddles = if packed {
+            (0..=half)
+                .map(|k| {
+                    let angle = -std::f64::consts::TAU * k as f64 / size as f64;
+                    Complex::new(
+                        T::from_f64(angle.cos()).unwrap(),
+                        T::from_f64(angle.sin()).unwrap(),
+                    )
+                })
+                .collect()
+        } else {
+            vec![]
+        };
+
+        let inverse_scratch_len = inverse_plan
+            .get_inplace_scratch_len()
+            .max(inverse_plan.get_outofplace_scratch_len());
+
         Self {
-            forward_scratch_buffer: vec![Complex::new(0.0,0.0); forward_plan.get_inplace_scratch_len()],
-            inverse_scratch_buffer: vec![Complex::new(0.0,0.0); inverse_plan.get_inplace_scratch_len()],
+            forward_scratch_buffer: vec![Complex::zero(); forward_plan.get_inplace_scratch_len()],
+            inverse_scratch_buffer: vec![Complex::zero(); inverse_scratch_len],
+            packed: vec![Complex::zero(); forward_plan.len()],
             forward_plan,
             inverse_plan,
+            twiddles,
         }
     }
 }
 
-impl FftBackend<Float> for RustFftBackend<Float> {
-    fn forward(&mut self, input: &[Float]) -> Vec<Complex> {
-        let mut out: Vec<Complex> = input.iter().map(|&x| Complex::new(x, 0.0)).collect();
-        self.forward_plan.process_with_scratch(out.as_mut_slice(), self.forward_scratch_buffer.as_mut_slice());
-        out
+impl<T: FftNum> FftBackend<T> for RustFftBackend<T> {
+    fn forward_into(&mut self, input: &[T], output: &mut [Complex<T>]) -> Result<(), FftError> {
+        let half = input.len() / 2;
+
+        if self.twiddles.is_empty() {
+            for (out, &x) in self.packed.iter_mut().zip(input) {
+                *out = Complex::new(x, T::zero());
+            }
+            self.forward_plan
+                .process_with_scratch(&mut self.packed, self.forward_scratch_buffer.as_mut_slice());
+            output.copy_from_slice(&self.packed[..=half]);
+            return Ok(());
+        }
+
+        // z[n] = x[2n] + i x[2n + 1]
+        for (out, pair) in self.packed.iter_mut().zip(input.chunks_exact(2)) {
+            *out = Complex::new(pair[0], pair[1]);
+        }
+        self.forward_plan
+            .process_with_scratch(&mut self.packed, self.forward_scratch_buffer.as_mut_slice());
+
+        // Z[k] is the sum of the even part E[k] and i times the odd part O[k], both
+        // Hermitian, so X[k] = E[k] + e^(-2 pi i k / size) O[k]
+        let two = T::one() + T::one();
+        for (k, out) in output.iter_mut().enumerate() {
+            let a = self.packed[k % half];
+            let b = self.packed[(half - k) % half].conj();
+            let even = (a + b) / two;
+            let odd = (a - b) * Complex::new(T::zero(), -T::one()) / two;
+            *out = even + self.twiddles[k] * odd;
+        }
+        Ok(())
+    }
+
+    fn inverse_in_place(&mut self, data: &mut [Complex<T>]) -> Result<(), FftError> {
+        self.inverse_plan
+            .process_with_scratch(data, self.inverse_scratch_buffer.as_mut_slice());
+        Ok(())
     }
 
-    fn inverse(&mut self, input: &mut [Complex]) -> Vec<Complex> {
-        let mut output = input.to_vec();
-        self.inverse_plan.process_with_scratch(output.as_mut_slice(), self.inverse_scratch_buffer.as_mut_slice());
-        output
+    fn inverse_into(
+        &mut self,
+        input: &mut [Complex<T>],
+        output: &mut [Complex<T>],
+    ) -> Result<(), FftError> {
+        self.inverse_plan.process_outofplace_with_scratch(
+            input,
+            output,
+            self.inverse_scratch_buffer.as_mut_slice(),
+        );
+        Ok(())
     }
 }
 
 /// FFTW3 Backend using the fftw crate
 #[cfg(feature = "fftw")]
-pub use fftw_backend::FftwBackend;
+pub use fftw_backend::{
+    export_wisdom, import_wisdom, FftwBackend, FftwError, FftwPlanner, FftwPlans, FftwRigor,
+};
 
 #[cfg(feature = "fftw")]
 mod fftw_backend {
     use super::*;
 
+    use std::ffi::{CStr, CString};
+    use std::fmt;
+    use std::path::{Path, PathBuf};
+
     use fftw::{
+        array::{alignment_of, AlignedVec},
         plan::*,
         types::*,
-        array::{AlignedAllocable, AlignedVec},
     };
 
-    pub struct FftwBackend<T: FftNum> {
+    /// FFTW plan types and wisdom for a floating point precision
+    pub trait FftwPlans {
+        type R2C: Send;
+        type C2C: Send;
 
-        forward_input_buffer: AlignedVec<T>,
-        forward_output_buffer: AlignedVec<Complex>,
+        /// Merge wisdom for this precision from the file at `path`, returning whether it succeeded
+        fn import_wisdom_from_filename(path: &CStr) -> bool;
+
+        /// Write all wisdom for this precision to the file at `path`, returning whether it succeeded
+        fn export_wisdom_to_filename(path: &CStr) -> bool;
+    }
 
-        inverse_input_buffer: AlignedVec<Complex>,
-        inverse_output_buffer: AlignedVec<Complex>,
+    impl FftwPlans for f32 {
+        type R2C = R2CPlan32;
+        type C2C = C2CPlan32;
 
-        forward_plan: R2CPlan32,
-        inverse_plan: C2CPlan32,
+        fn import_wisdom_from_filename(path: &CStr) -> bool {
+            // SAFETY: `path` is a valid NUL terminated string, and callers hold `FFTW_MUTEX`
+            unsafe { fftw_sys::fftwf_import_wisdom_from_filename(path.as_ptr()) != 0 }
+        }
+
+        fn export_wisdom_to_filename(path: &CStr) -> bool {
+            // SAFETY: as above
+            unsafe { fftw_sys::fftwf_export_wisdom_to_filename(path.as_ptr()) != 0 }
+        }
+    }
+
+    impl FftwPlans for f64 {
+        type R2C = R2CPlan64;
+        type C2C = C2CPlan64;
+
+        fn import_wisdom_from_filename(path: &CStr) -> bool {
+            // SAFETY: as above
+            unsafe { fftw_sys::fftw_import_wisdom_from_filename(path.as_ptr()) != 0 }
+        }
+
+        fn export_wisdom_to_filename(path: &CStr) -> bool {
+            // SAFETY: as above
+            unsafe { fftw_sys::fftw_export_wisdom_to_filename(path.as_ptr()) != 0 }
+        }
     }
 
-    impl <T: FftNum+AlignedAllocable+Default> FftwBackend<T> {
-        pub fn new(size: usize) -> Self {
+    /// Errors from planning or executing FFTW transforms, or reading and writing wisdom
+    #[derive(Debug)]
+    pub enum FftwError {
+        /// FFTW could not plan a transform of `size` samples
+        Plan {
+            size: usize,
+            source: fftw::error::Error,
+        },
+        /// A buffer passed to a transform has the wrong length for the plan
+        Length { expected: usize, actual: usize },
+        /// FFTW refused to execute a plan on the given buffers
+        Execute(fftw::error::Error),
+        /// Wisdom could not be read from or written to the file
+        Wisdom(PathBuf),
+    }
 
-            // Allocate buffers for real to complex forward transform. Output length is size/2+1
-            let forward_input_buffer = AlignedVec::new(size);
-            let forward_output_buffer = AlignedVec::new((size>>1) + 1);
+    impl fmt::Display for FftwError {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match self {
+                Self::Plan { size, source } => {
+                    write!(
+                        f,
+                        "failed to plan an FFTW transform of size {size}: {source}"
+                    )
+                }
+                Self::Length { expected, actual } => {
+                    write!(f, "FFTW buffer has length {actual}, expected {expected}")
+                }
+                Self::Execute(source) => write!(f, "failed to execute an FFTW plan: {source}"),
+                Self::Wisdom(path) => {
+                    write!(f, "failed to access FFTW wisdom at {}", path.display())
+                }
+            }
+        }
+    }
 
-            let inverse_input_buffer = AlignedVec::new(size);
-            let inverse_output_buffer = AlignedVec::new(size);
+    impl std::error::Error for FftwError {
+        fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+            match self {
+                Self::Plan { source, .. } | Self::Execute(source) => Some(source),
+                Self::Length { .. } | Self::Wisdom(_) => None,
+            }
+        }
+    }
 
-            // Create plans
-            let forward_plan = R2CPlan::aligned(&[size], Flag::ESTIMATE).unwrap();
-            let inverse_plan = C2CPlan::aligned(&[size], Sign::Backward, Flag::ESTIMATE).unwrap();
+    /// How much time FFTW spends searching for the fastest plan.
+    ///
+    /// Anything above [`FftwRigor::Estimate`] times candidate plans on the machine, which
+    /// can take seconds for large sizes. The results are kept as wisdom, which
+    /// [`export_wisdom`] saves so later processes can [`import_wisdom`] instead of measuring again.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+    pub enum FftwRigor {
+        /// Pick a plan from heuristics, without measuring
+        #[default]
+        Estimate,
+        /// Measure a few candidate plans
+        Measure,
+        /// Measure a wider range of plans
+        Patient,
+        /// Measure every plan FFTW knows
+        Exhaustive,
+    }
 
-            Self {
-                forward_input_buffer,
-                forward_output_buffer,
-                inverse_input_buffer,
-                inverse_output_buffer,
-                forward_plan,
-                inverse_plan,
+    impl FftwRigor {
+        fn flag(self) -> Flag {
+            match self {
+                Self::Estimate => Flag::ESTIMATE,
+                Self::Measure => Flag::MEASURE,
+                Self::Patient => Flag::PATIENT,
+                Self::Exhaustive => Flag::EXHAUSIVE,
             }
         }
     }
 
-    impl FftBackend<f32> for FftwBackend<f32> {
-        fn forward(&mut self, input: &[f32]) -> Vec<Complex> {
-            self.forward_input_buffer.copy_from_slice(input);
-            self.forward_plan.r2c(&mut self.forward_input_buffer, &mut self.forward_output_buffer).unwrap();
+    fn wisdom_path(path: &Path) -> Result<CString, FftwError> {
+        path.to_str()
+            .and_then(|s| CString::new(s).ok())
+            .ok_or_else(|| FftwError::Wisdom(path.to_path_buf()))
+    }
 
-            let mut out = vec![Complex::new(0.0,0.0); input.len()];
+    /// Merge FFTW wisdom for precision `T` from the file at `path`, so that plans made
+    /// afterwards reuse earlier measurements
+    pub fn import_wisdom<T: FftwPlans>(path: impl AsRef<Path>) -> Result<(), FftwError> {
+        let path = path.as_ref();
+        let name = wisdom_path(path)?;
 
-            out[0..(input.len()>>1) + 1].copy_from_slice(self.forward_output_buffer.as_slice());
+        // Wisdom is shared with the planner, which the fftw crate serializes on this mutex
+        let _guard = fftw::FFTW_MUTEX.lock().unwrap();
+        if T::import_wisdom_from_filename(&name) {
+            Ok(())
+        } else {
+            Err(FftwError::Wisdom(path.to_path_buf()))
+        }
+    }
 
-            // Make the FFT output symmetrical
-            for i in 1..(input.len() >> 1) {
-                out[input.len() - i].re = out[i].re;
-                out[input.len() - i].im = -out[i].im;
+    /// Save the FFTW wisdom gathered for precision `T` to the file at `path`
+    pub fn export_wisdom<T: FftwPlans>(path: impl AsRef<Path>) -> Result<(), FftwError> {
+        let path = path.as_ref();
+        let name = wisdom_path(path)?;
+        let _guard = fftw::FFTW_MUTEX.lock().unwrap();
+        if T::export_wisdom_to_filename(&name) {
+            Ok(())
+        } else {
+            Err(FftwError::Wisdom(path.to_path_buf()))
+        }
+    }
+
+    /// Planner for [`FftwBackend`]s, planning with the given rigor
+    #[derive(Debug, Clone, Copy, Default)]
+    pub struct FftwPlanner {
+        rigor: FftwRigor,
+    }
+
+    impl FftwPlanner {
+        pub fn new(rigor: FftwRigor) -> Self {
+            Self { rigor }
+        }
+
+        /// Rigor the backends are planned with
+        #[inline]
+        pub fn rigor(&self) -> FftwRigor {
+            self.rigor
+        }
+    }
+
+    pub struct FftwBackend<T: FftwPlans> {
+        forward_input_buffer: AlignedVec<T>,
+        forward_output_buffer: AlignedVec<Complex<T>>,
+
+        inverse_input_buffer: AlignedVec<Complex<T>>,
+        inverse_output_buffer: AlignedVec<Complex<T>>,
+
+        forward_plan: T::R2C,
+        inverse_plan: T::C2C,
+    }
+
+    fn check_len(expected: usize, actual: usize) -> Result<(), FftwError> {
+        if expected == actual {
+            Ok(())
+        } else {
+            Err(FftwError::Length { expected, actual })
+        }
+    }
+
+    macro_rules! impl_fftw_backend {
+        ($t:ty) => {
+            impl FftwBackend<$t> {
+                /// Backend planned with [`FftwRigor::Estimate`]
+                pub fn new(size: usize) -> Result<Self, FftwError> {
+                    Self::with_rigor(size, FftwRigor::Estimate)
+                }
+
+                /// Backend planned with `rigor`, which may take a while unless the wisdom
+                /// for `size` was imported
+                pub fn with_rigor(size: usize, rigor: FftwRigor) -> Result<Self, FftwError> {
+                    let error = |source| FftwError::Plan { size, source };
+
+                    // Allocate buffers for real to complex forward transform. Output length is size/2+1
+                    let forward_input_buffer = AlignedVec::new(size);
+                    let forward_output_buffer = AlignedVec::new((size >> 1) + 1);
+
+                    let inverse_input_buffer = AlignedVec::new(size);
+                    let inverse_output_buffer = AlignedVec::new(size);
+
+                    // Create plans. Measuring plans overwrites scratch arrays, not the buffers.
+                    let forward_plan = R2CPlan::aligned(&[size], rigor.flag()).map_err(error)?;
+                    let inverse_plan =
+                        C2CPlan::aligned(&[size], Sign::Backward, rigor.flag()).map_err(error)?;
+
+                    Ok(Self {
+                        forward_input_buffer,
+                        forward_output_buffer,
+                        inverse_input_buffer,
+                        inverse_output_buffer,
+                        forward_plan,
+                        inverse_plan,
+                    })
+                }
+
+                /// Inverse transform of the input buffer into `output`, straight if it is
+                /// aligned and through the output buffer otherwise
+                fn inverse_buffered(
+                    &mut self,
+                    output: &mut [Complex<$t>],
+                ) -> Result<(), FftwError> {
+                    check_len(self.inverse_output_buffer.len(), output.len())?;
+
+                    if alignment_of(output) == 0 {
+                        self.inverse_plan
+                            .c2c(&mut self.inverse_input_buffer, output)
+                            .map_err(FftwError::Execute)
+                    } else {
+                        self.inverse_plan
+                            .c2c(
+                                &mut self.inverse_input_buffer,
+                                &mut self.inverse_output_buffer,
+                            )
+                            .map_err(FftwError::Execute)?;
+                        output.copy_from_slice(&self.inverse_output_buffer);
+                        Ok(())
+                    }
+                }
+            }
+
+            impl FftPlanner<$t> for FftwPlanner {
+                fn plan(&self, size: usize) -> PlanResult<$t> {
+                    Ok(Box::new(FftwBackend::<$t>::with_rigor(size, self.rigor)?))
+                }
+            }
+
+            impl FftBackend<$t> for FftwBackend<$t> {
+                fn forward_into(
+                    &mut self,
+                    input: &[$t],
+                    output: &mut [Complex<$t>],
+                ) -> Result<(), FftError> {
+                    check_len(self.forward_input_buffer.len(), input.len())?;
+                    check_len(self.forward_output_buffer.len(), output.len())?;
+                    self.forward_input_buffer.copy_from_slice(input);
+
+                    // The plan only executes on buffers aligned like the ones it was planned
+                    // for, so unaligned outputs go through the output buffer
+                    if alignment_of(output) == 0 {
+                        self.forward_plan
+                            .r2c(&mut self.forward_input_buffer, output)
+                            .map_err(FftwError::Execute)?;
+                    } else {
+                        self.forward_plan
+                            .r2c(
+                                &mut self.forward_input_buffer,
+                                &mut self.forward_output_buffer,
+                            )
+                            .map_err(FftwError::Execute)?;
+                        output.copy_from_slice(&self.forward_output_buffer);
+                    }
+                    Ok(())
+                }
+
+                fn inverse_in_place(&mut self, data: &mut [Complex<$t>]) -> Result<(), FftError> {
+                    check_len(self.inverse_input_buffer.len(), data.len())?;
+                    self.inverse_input_buffer.copy_from_slice(data);
+                    Ok(self.inverse_buffered(data)?)
+                }
+
+                fn inverse_into(
+                    &mut self,
+                    input: &mut [Complex<$t>],
+                    output: &mut [Complex<$t>],
+                ) -> Result<(), FftError> {
+                    check_len(self.inverse_input_buffer.len(), input.len())?;
+
+                    if alignment_of(input) == 0 && alignment_of(output) == 0 {
+                        check_len(self.inverse_output_buffer.len(), output.len())?;
+                        self.inverse_plan
+                            .c2c(input, output)
+                            .map_err(FftwError::Execute)?;
+                    } else {
+                        self.inverse_input_buffer.copy_from_slice(input);
+                        self.inverse_buffered(output)?;
+                    }
+                    Ok(())
+                }
             }
+        };
+    }
 
-            out
+    impl_fftw_backend!(f32);
+    impl_fftw_backend!(f64);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rustfft_forward_into_half_spectrum() {
+        for size in [1, 2, 3, 8, 15, 64, 100] {
+            let input: Vec<f64> = (0..size).map(|i| ((i * 7 + 3) % 11) as f64 - 5.0).collect();
+
+            // Reference spectrum from the full complex transform
+            let mut expected: Vec<Complex<f64>> =
+                input.iter().map(|&x| Complex::new(x, 0.0)).collect();
+            rustfft::FftPlanner::new()
+                .plan_fft_forward(size)
+                .process(&mut expected);
+
+            let mut fft = RustFftBackend::<f64>::new(size);
+            let mut half = vec![Complex::zero(); size / 2 + 1];
+            fft.forward_into(&input, &mut half).unwrap();
+            for (x, y) in half.iter().zip(&expected) {
+                assert!((x - y).norm() < 1e-9);
+            }
         }
+    }
+
+    #[test]
+    fn test_rustfft_inverse() {
+        let size = 64;
+        let spectrum: Vec<Complex<f64>> = (0..size)
+            .map(|k| Complex::new(((k * 5) % 7) as f64, ((k * 3) % 4) as f64 - 1.5))
+            .collect();
+
+        let mut expected = spectrum.clone();
+        rustfft::FftPlanner::new()
+            .plan_fft_inverse(size)
+            .process(&mut expected);
+
+        let mut fft = RustFftBackend::<f64>::new(size);
 
-        fn inverse(&mut self, input: &mut [Complex]) -> Vec<Complex> {
-            self.inverse_input_buffer.copy_from_slice(input);
-            self.inverse_plan.c2c(&mut self.inverse_input_buffer, &mut self.inverse_output_buffer).unwrap();
-            self.inverse_output_buffer.to_vec()
+        let mut data = spectrum.clone();
+        fft.inverse_in_place(&mut data).unwrap();
+        assert_eq!(data, expected);
+
+        let mut input = spectrum.clone();
+        let mut output = vec![Complex::zero(); size];
+        fft.inverse_into(&mut input, &mut output).unwrap();
+        for (x, y) in output.iter().zip(&expected) {
+            assert!((x - y).norm() < 1e-9);
         }
     }
-}
\ No newline at end of file
+
+    #[cfg(feature = "fftw")]
+    #[test]
+    fn test_fftw_wisdom() {
+        let path = std::env::temp_dir().join(format!("fcwt-wisdom-{}", std::process::id()));
+
+        FftwBackend::<f64>::with_rigor(256, FftwRigor::Measure).unwrap();
+        export_wisdom::<f64>(&path).unwrap();
+        import_wisdom::<f64>(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert!(matches!(
+            import_wisdom::<f64>(&path),
+            Err(FftwError::Wisdom(_))
+        ));
+    }
+
+    #[cfg(feature = "fftw")]
+    #[test]
+    fn test_fftw_length_error() {
+        let mut fft = FftwBackend::<f64>::new(64).unwrap();
+        let mut output = vec![Complex::zero(); 64];
+
+        let error = fft.forward_into(&[0.0; 64], &mut output).unwrap_err();
+        assert!(matches!(
+            error.downcast_ref::<FftwError>(),
+            Some(FftwError::Length {
+                expected: 33,
+                actual: 64
+            })
+        ));
+    }
+}
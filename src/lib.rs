@@ -1,17 +1,59 @@
-pub type Float = f32;
-pub type Complex = rustfft::num_complex::Complex<Float>;
-
-const IPI4: Float = 0.75112554446;
-
-pub mod fft;
-pub mod wavelet;
-pub mod scales;
-pub mod fcwt;
-pub mod result;
-
-pub mod util;
-
-pub use wavelet::MorletWavelet;
-pub use scales::{Scales, LinFreqs};
-pub use fcwt::FastCwt;
-pub use result::CwtResult;
\ No newline at end of file
+use rustfft::num_traits::{Float as NumFloat, FloatConst};
+use rustfft::FftNum;
+
+pub use rustfft::num_complex::Complex;
+
+use fft::PlanResult;
+
+/// Floating point precision a transform is computed in, implemented for `f32` and `f64`
+pub trait Float: FftNum + NumFloat + FloatConst + Default {
+    /// Plan the FFT backend selected by the crate features for a transform of `size` samples
+    fn default_backend(size: usize) -> PlanResult<Self>;
+}
+
+macro_rules! impl_float {
+    ($t:ty) => {
+        impl Float for $t {
+            #[cfg(feature = "fftw")]
+            fn default_backend(size: usize) -> PlanResult<Self> {
+                Ok(Box::new(fft::FftwBackend::<$t>::new(size)?))
+            }
+
+            #[cfg(not(feature = "fftw"))]
+            fn default_backend(size: usize) -> PlanResult<Self> {
+                Ok(Box::new(fft::RustFftBackend::<$t>::new(size)))
+            }
+        }
+    };
+}
+
+impl_float!(f32);
+impl_float!(f64);
+
+/// Convert a constant to the precision of the transform
+#[inline(always)]
+pub(crate) fn cast<T: Float>(value: f64) -> T {
+    T::from_f64(value).unwrap()
+}
+
+const IPI4: f64 = 0.75112554446;
+
+pub mod fft;
+pub mod wavelet;
+pub mod scales;
+pub mod fcwt;
+pub mod padding;
+pub mod result;
+
+pub mod util;
+
+pub use wavelet::{BumpWavelet, CustomWavelet, DogWavelet, FbspWavelet, MorletWavelet, MorseWavelet, PaulWavelet, ShannonWavelet};
+pub use scales::{Scales, LinFreqs, LinScales, LogFreqs, LogScales, OctaveScales};
+pub use fcwt::{ConvolutionMode, FastCwt, SamplingMode};
+pub use padding::PaddingMode;
+pub use result::CwtResult;
+
+// Compile the README examples as doctests
+#[cfg(doctest)]
+#[doc = include_str!("../README.md")]
+struct ReadmeDoctests;
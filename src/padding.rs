@@ -0,0 +1,150 @@
+use crate::Float;
+
+/// Signal extension used to pad inputs up to the FFT size.
+///
+/// Examples show the extension of the signal `a b c d` on both sides.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PaddingMode {
+    /// Extend with zeros: `0 0 | a b c d | 0 0`
+    #[default]
+    Zero,
+    /// Mirror about the edge samples without repeating them: `c b | a b c d | c b`
+    Reflect,
+    /// Mirror about the edges, repeating the edge samples: `b a | a b c d | d c`
+    Symmetric,
+    /// Wrap around the signal: `c d | a b c d | a b`
+    Periodic,
+    /// Repeat the edge samples: `a a | a b c d | d d`
+    Edge,
+}
+
+impl PaddingMode {
+    /// Map an index into the padded signal, relative to the start of the input,
+    /// onto an index into the input. Returns `None` where the padding is zero.
+    pub(crate) fn source_index(&self, index: isize, len: usize) -> Option<usize> {
+        let n = len as isize;
+
+        if (0..n).contains(&index) {
+            return Some(index as usize);
+        }
+
+        match self {
+            PaddingMode::Zero => None,
+            PaddingMode::Edge => Some(index.clamp(0, n - 1) as usize),
+            PaddingMode::Periodic => Some(index.rem_euclid(n) as usize),
+            PaddingMode::Symmetric => {
+                let i = index.rem_euclid(2 * n);
+                Some(if i < n { i } else { 2 * n - 1 - i } as usize)
+            }
+            PaddingMode::Reflect => {
+                if n == 1 {
+                    return Some(0);
+                }
+                let period = 2 * (n - 1);
+                let i = index.rem_euclid(period);
+                Some(if i < n { i } else { period - i } as usize)
+            }
+        }
+    }
+
+    /// Pad `input` into `output`, centering the input and extending it on both sides.
+    ///
+    /// Returns the offset of the first input sample in `output`.
+    pub fn pad<T: Float>(&self, input: &[T], output: &mut [T]) -> usize {
+        assert!(
+            output.len() >= input.len(),
+            "padded length must not be shorter than the input"
+        );
+
+        if input.is_empty() {
+            output.fill(T::zero());
+            return 0;
+        }
+
+        let offset = (output.len() - input.len()) / 2;
+
+        for (i, value) in output.iter_mut().enumerate() {
+            *value = self
+                .source_index(i as isize - offset as isize, input.len())
+                .map_or(T::zero(), |j| input[j]);
+        }
+
+        offset
+    }
+}
+
+/// Length an input of `len` samples is padded to before transforming
+#[inline]
+pub fn padded_len(len: usize) -> usize {
+    len.next_power_of_two()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pad(mode: PaddingMode, input: &[f32], len: usize) -> (Vec<f32>, usize) {
+        let mut output = vec![-1.0; len];
+        let offset = mode.pad(input, &mut output);
+        (output, offset)
+    }
+
+    #[test]
+    fn test_padding_modes() {
+        let input = [1.0, 2.0, 3.0, 4.0];
+
+        assert_eq!(
+            pad(PaddingMode::Zero, &input, 8),
+            (vec![0.0, 0.0, 1.0, 2.0, 3.0, 4.0, 0.0, 0.0], 2)
+        );
+        assert_eq!(
+            pad(PaddingMode::Reflect, &input, 8),
+            (vec![3.0, 2.0, 1.0, 2.0, 3.0, 4.0, 3.0, 2.0], 2)
+        );
+        assert_eq!(
+            pad(PaddingMode::Symmetric, &input, 8),
+            (vec![2.0, 1.0, 1.0, 2.0, 3.0, 4.0, 4.0, 3.0], 2)
+        );
+        assert_eq!(
+            pad(PaddingMode::Periodic, &input, 8),
+            (vec![3.0, 4.0, 1.0, 2.0, 3.0, 4.0, 1.0, 2.0], 2)
+        );
+        assert_eq!(
+            pad(PaddingMode::Edge, &input, 8),
+            (vec![1.0, 1.0, 1.0, 2.0, 3.0, 4.0, 4.0, 4.0], 2)
+        );
+    }
+
+    #[test]
+    fn test_padding_longer_than_input() {
+        // Extensions wider than the input keep folding back onto it
+        let input = [1.0, 2.0, 3.0];
+
+        assert_eq!(
+            pad(PaddingMode::Reflect, &input, 11),
+            (
+                vec![1.0, 2.0, 3.0, 2.0, 1.0, 2.0, 3.0, 2.0, 1.0, 2.0, 3.0],
+                4
+            )
+        );
+        assert_eq!(
+            pad(PaddingMode::Symmetric, &input, 11),
+            (
+                vec![3.0, 3.0, 2.0, 1.0, 1.0, 2.0, 3.0, 3.0, 2.0, 1.0, 1.0],
+                4
+            )
+        );
+        assert_eq!(
+            pad(PaddingMode::Reflect, &[5.0], 4),
+            (vec![5.0, 5.0, 5.0, 5.0], 1)
+        );
+    }
+
+    #[test]
+    fn test_padded_len() {
+        assert_eq!(padded_len(1), 1);
+        assert_eq!(padded_len(5), 8);
+        assert_eq!(padded_len(1024), 1024);
+        assert_eq!(padded_len(1025), 2048);
+    }
+}
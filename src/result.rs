@@ -1,25 +1,54 @@
-use rustfft::num_complex::Complex;
+use core::iter::StepBy;
 use core::ops::{Index, IndexMut};
+use core::slice::{Iter, IterMut};
 use rayon::prelude::*;
+use rustfft::num_traits::Zero;
 
+use crate::padding::padded_len;
+use crate::{cast, Complex, Float};
 
+/// Transform output stored as a single contiguous row-major buffer,
+/// with one row of `num_samples()` values per scale.
 pub struct CwtResult<T> {
     scales: usize,
     samples: usize,
-    data: Vec<Vec<Complex<T>>>,
+    data: Vec<Complex<T>>,
 }
 
-impl CwtResult<f32> {
+impl<T: Float> CwtResult<T> {
+    /// Create a zeroed result with the given number of scales and samples per scale
     #[inline]
     pub fn new(scales: usize, samples: usize) -> Self {
-        let data = Vec::with_capacity(scales*samples);
+        let data = vec![Complex::zero(); scales * samples];
         Self {
             scales,
             samples,
-            data
+            data,
         }
     }
 
+    /// Wrap a row-major buffer of `scales * samples` values
+    pub fn from_vec(scales: usize, samples: usize, data: Vec<Complex<T>>) -> Self {
+        assert_eq!(
+            data.len(),
+            scales * samples,
+            "data length must equal scales * samples"
+        );
+        Self {
+            scales,
+            samples,
+            data,
+        }
+    }
+
+    /// Resize to the given number of scales and samples per scale.
+    /// Does not allocate if the total size is unchanged.
+    pub fn resize(&mut self, scales: usize, samples: usize) {
+        self.data.resize(scales * samples, Complex::zero());
+        self.scales = scales;
+        self.samples = samples;
+    }
+
     /// Get the number of scales
     #[inline]
     pub fn num_scales(&self) -> usize {
@@ -32,43 +61,278 @@ impl CwtResult<f32> {
         self.samples
     }
 
+    /// Distance between consecutive rows in the flat buffer
+    #[inline]
+    pub fn stride(&self) -> usize {
+        self.samples
+    }
+
+    /// Get the row of a scale
     #[inline]
-    pub fn rows(&self) -> &Vec<Vec<Complex<f32>>> {
+    pub fn row(&self, scale: usize) -> &[Complex<T>] {
+        &self.data[scale * self.samples..(scale + 1) * self.samples]
+    }
+
+    /// Get the mutable row of a scale
+    #[inline]
+    pub fn row_mut(&mut self, scale: usize) -> &mut [Complex<T>] {
+        &mut self.data[scale * self.samples..(scale + 1) * self.samples]
+    }
+
+    /// Iterate over the rows of all scales. Yields `num_scales()` rows, which are
+    /// empty when there are no samples.
+    #[inline]
+    pub fn rows(&self) -> impl DoubleEndedIterator<Item = &[Complex<T>]> + ExactSizeIterator {
+        (0..self.scales).map(move |scale| self.row(scale))
+    }
+
+    /// Iterate mutably over the rows of all scales, like [`Self::rows`]
+    #[inline]
+    pub fn rows_mut(&mut self) -> RowsMut<'_, T> {
+        RowsMut {
+            rest: &mut self.data,
+            samples: self.samples,
+            remaining: self.scales,
+        }
+    }
+
+    /// Iterate mutably over the rows of all scales in parallel
+    #[inline]
+    pub(crate) fn par_rows_mut(&mut self) -> rayon::slice::ChunksExactMut<'_, Complex<T>> {
+        self.data.par_chunks_exact_mut(self.samples.max(1))
+    }
+
+    /// Iterate over the values of all scales at a sample index
+    #[inline]
+    pub fn column(&self, sample: usize) -> StepBy<Iter<'_, Complex<T>>> {
+        assert!(sample < self.samples, "sample index must be in bounds");
+        self.data[sample..].iter().step_by(self.samples)
+    }
+
+    /// Iterate mutably over the values of all scales at a sample index
+    #[inline]
+    pub fn column_mut(&mut self, sample: usize) -> StepBy<IterMut<'_, Complex<T>>> {
+        assert!(sample < self.samples, "sample index must be in bounds");
+        self.data[sample..].iter_mut().step_by(self.samples)
+    }
+
+    /// Get the flat row-major buffer
+    #[inline]
+    pub fn as_slice(&self) -> &[Complex<T>] {
         &self.data
     }
 
+    /// Get the mutable flat row-major buffer
     #[inline]
-    pub fn push_row(&mut self, value: Vec<Complex<f32>>) {
-        self.data.push(value);
+    pub fn as_mut_slice(&mut self) -> &mut [Complex<T>] {
+        &mut self.data
     }
 
+    /// Take ownership of the flat row-major buffer
     #[inline]
-    pub fn clear(&mut self) {
-        self.data.clear();
+    pub fn into_vec(self) -> Vec<Complex<T>> {
+        self.data
     }
 
+    /// Divide by the padded FFT length, which turns the output of an unnormalized
+    /// transform into the normalized one
     pub fn normalize(&mut self) {
-        self.data.par_iter_mut().for_each(|row| {
-            let size = row.len() as f32;
+        let size: T = cast(padded_len(self.samples) as f64);
 
-            row.par_iter_mut().for_each(|field| {
-                *field = field.unscale(size);
-            });
+        self.data.par_iter_mut().for_each(|field| {
+            *field = field.unscale(size);
         });
     }
-
 }
 
-impl Index<usize> for CwtResult<f32> {
-    type Output = [Complex<f32>];
-    
+impl<T: Float> Index<usize> for CwtResult<T> {
+    type Output = [Complex<T>];
+
     fn index(&self, y: usize) -> &Self::Output {
-        &self.data[y]
+        self.row(y)
     }
 }
 
-impl IndexMut<usize> for CwtResult<f32> {    
+impl<T: Float> IndexMut<usize> for CwtResult<T> {
     fn index_mut(&mut self, y: usize) -> &mut Self::Output {
-        &mut self.data[y]
+        self.row_mut(y)
+    }
+}
+
+impl<T: Float> Index<(usize, usize)> for CwtResult<T> {
+    type Output = Complex<T>;
+
+    fn index(&self, (y, x): (usize, usize)) -> &Self::Output {
+        &self.row(y)[x]
+    }
+}
+
+impl<T: Float> IndexMut<(usize, usize)> for CwtResult<T> {
+    fn index_mut(&mut self, (y, x): (usize, usize)) -> &mut Self::Output {
+        &mut self.row_mut(y)[x]
+    }
+}
+
+/// Mutable iterator over the rows of a [`CwtResult`], returned by [`CwtResult::rows_mut`]
+pub struct RowsMut<'a, T> {
+    rest: &'a mut [Complex<T>],
+    samples: usize,
+    remaining: usize,
+}
+
+impl<'a, T> Iterator for RowsMut<'a, T> {
+    type Item = &'a mut [Complex<T>];
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        self.remaining -= 1;
+        let (row, rest) = core::mem::take(&mut self.rest).split_at_mut(self.samples);
+        self.rest = rest;
+        Some(row)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<T> DoubleEndedIterator for RowsMut<'_, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        self.remaining -= 1;
+        let rest = core::mem::take(&mut self.rest);
+        let (rest, row) = rest.split_at_mut(rest.len() - self.samples);
+        self.rest = rest;
+        Some(row)
+    }
+}
+
+impl<T> ExactSizeIterator for RowsMut<'_, T> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{FastCwt, LogScales, MorletWavelet};
+
+    fn numbered(scales: usize, samples: usize) -> CwtResult<f32> {
+        let data = (0..scales * samples)
+            .map(|i| Complex::new(i as f32, 0.0))
+            .collect();
+        CwtResult::from_vec(scales, samples, data)
+    }
+
+    #[test]
+    fn test_cwt_result_layout() {
+        let result = numbered(3, 4);
+
+        assert_eq!(result.as_slice().len(), 12);
+        assert_eq!(result.stride(), 4);
+        assert_eq!(result.rows().len(), 3);
+        assert_eq!(result.row(1), &result.as_slice()[4..8]);
+        assert_eq!(result[2][3], Complex::new(11.0, 0.0));
+        assert_eq!(result[(2, 3)], Complex::new(11.0, 0.0));
+
+        let column: Vec<f32> = result.column(1).map(|c| c.re).collect();
+        assert_eq!(column, vec![1.0, 5.0, 9.0]);
+    }
+
+    #[test]
+    fn test_cwt_result_empty_rows() {
+        let mut result = CwtResult::<f32>::new(3, 0);
+
+        assert_eq!(result.num_scales(), 3);
+        assert_eq!(result.rows().len(), 3);
+        assert!(result.rows().all(|row| row.is_empty()));
+        assert_eq!(result.rows_mut().len(), 3);
+        assert!(result.rows_mut().all(|row| row.is_empty()));
+    }
+
+    #[test]
+    fn test_cwt_result_rows_mut() {
+        let mut result = numbered(3, 2);
+
+        let mut rows = result.rows_mut();
+        assert_eq!(rows.len(), 3);
+        assert_eq!(rows.next_back().unwrap()[0], Complex::new(4.0, 0.0));
+        assert_eq!(rows.next().unwrap()[1], Complex::new(1.0, 0.0));
+        assert_eq!(rows.len(), 1);
+        rows.next().unwrap().fill(Complex::zero());
+        assert!(rows.next().is_none() && rows.next_back().is_none());
+
+        assert_eq!(result.row(1), &[Complex::zero(); 2]);
+    }
+
+    #[test]
+    fn test_cwt_result_mut() {
+        let mut result = CwtResult::<f32>::new(2, 3);
+
+        result.row_mut(1).fill(Complex::new(1.0, 0.0));
+        result.column_mut(0).for_each(|c| c.im = 2.0);
+        result[(0, 2)] = Complex::new(3.0, 0.0);
+
+        assert_eq!(
+            result.into_vec(),
+            vec![
+                Complex::new(0.0, 2.0),
+                Complex::zero(),
+                Complex::new(3.0, 0.0),
+                Complex::new(1.0, 2.0),
+                Complex::new(1.0, 0.0),
+                Complex::new(1.0, 0.0),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_cwt_result_resize() {
+        let mut result = numbered(3, 4);
+        let ptr = result.as_slice().as_ptr();
+
+        // Reshaping to the same total size reuses the buffer
+        result.resize(4, 3);
+        assert_eq!(result.num_scales(), 4);
+        assert_eq!(result.num_samples(), 3);
+        assert_eq!(result.rows().len(), 4);
+        assert_eq!(result.as_slice().as_ptr(), ptr);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_cwt_result_index_out_of_row() {
+        let result = numbered(3, 4);
+
+        // Would be result[(2, 0)] in the flat buffer
+        let _ = result[(1, 4)];
+    }
+
+    #[test]
+    fn test_cwt_result_normalize() {
+        let mut result = numbered(2, 4);
+        result.normalize();
+        assert_eq!(result[(1, 0)], Complex::new(1.0, 0.0));
+
+        // 1000 samples are padded to 1024
+        let mut result = numbered(2, 1000);
+        result.normalize();
+        assert_eq!(result[(0, 512)], Complex::new(0.5, 0.0));
+    }
+
+    #[test]
+    fn test_cwt_result_normalize_matches_transform() {
+        let input = crate::util::chirp::<f32>(1000.0, 1000, 5.0, 50.0);
+        let transform = |normalize| {
+            let scales = LogScales::new(1000, 4.0, 200.0, 16);
+            FastCwt::new(MorletWavelet::new(1.0), scales, normalize).cwt(&input)
+        };
+
+        let mut result = transform(false);
+        result.normalize();
+        for (x, y) in result.as_slice().iter().zip(transform(true).as_slice()) {
+            assert!((x - y).norm() < 1e-5);
+        }
     }
 }
@@ -1,35 +1,198 @@
-type Float = super::Float;
+use crate::{cast, wavelet::Wavelet, Float};
 
-pub trait Scales {
+pub trait Scales<T: Float = f32> {
     fn len(&self) -> usize;
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
     fn sample_rate(&self) -> usize;
-    fn scale(&self, index: usize) -> Float;
-    fn freq(&self, index: usize) -> Float;
+    fn scale(&self, index: usize) -> T;
+    fn freq(&self, index: usize) -> T;
+
+    /// Equivalent Fourier frequency of the scale at `index` for `wavelet` (Torrence & Compo,
+    /// 1998), rather than the peak frequency of the daughter wavelet returned by [`Scales::freq`]
+    fn fourier_freq<W: Wavelet<T> + ?Sized>(&self, index: usize, wavelet: &W) -> T
+    where
+        Self: Sized,
+    {
+        self.freq(index) / wavelet.four_wavelen()
+    }
+}
+
+/// Validate a frequency range against the sample rate
+fn check_freq_range<T: Float>(sample_rate: usize, start_freq: T, end_freq: T) {
+    assert!(
+        start_freq < end_freq,
+        "start frequency must be lower than the end frequency"
+    );
+    // Ensure end freq is below Nyquist frequency (sample rate/2)
+    assert!(end_freq <= cast((sample_rate / 2) as f64));
+}
+
+/// Implement [`Scales`] for a type storing its scales in a `scales` vector
+macro_rules! impl_scales {
+    ($name:ident) => {
+        impl<T: Float> Scales<T> for $name<T> {
+            #[inline(always)]
+            fn len(&self) -> usize {
+                self.scales.len()
+            }
+
+            #[inline(always)]
+            fn sample_rate(&self) -> usize {
+                self.sample_rate
+            }
+
+            #[inline(always)]
+            fn scale(&self, index: usize) -> T {
+                self.scales[index]
+            }
+
+            #[inline(always)]
+            fn freq(&self, index: usize) -> T {
+                assert!(
+                    index < self.scales.len(),
+                    "Frequency Index must be in bounds"
+                );
+                cast::<T>(self.sample_rate as f64) / self.scales[index]
+            }
+        }
+    };
 }
 
+/// Linearly spaced frequencies, matching fCWT's `FCWT_LINFREQS`
 #[derive(Debug, Clone)]
-pub struct LinFreqs {
-    scales: Vec<Float>,
+pub struct LinFreqs<T: Float = f32> {
+    scales: Vec<T>,
     sample_rate: usize,
 }
 
-impl LinFreqs {
-    pub fn new(sample_rate: usize, start_freq: Float, end_freq: Float, size: usize) -> Self {
-        assert!(
-            start_freq < end_freq,
-            "start frequency must be lower than the end frequency"
-        );
-        // Ensure end freq is below Nyquist frequency (sample rate/2)
-        assert!(end_freq <= (sample_rate / 2) as Float);
+impl<T: Float> LinFreqs<T> {
+    pub fn new(sample_rate: usize, start_freq: T, end_freq: T, size: usize) -> Self {
+        check_freq_range(sample_rate, start_freq, end_freq);
 
-        let mut scales: Vec<Float> = vec![0.0; size];
+        let mut scales: Vec<T> = vec![T::zero(); size];
 
         // frequency delta
         let df = end_freq - start_freq;
+        let fs: T = cast(sample_rate as f64);
+        let n: T = cast(size as f64);
+
+        for i in 0..size {
+            scales[size - i - 1] = fs / (start_freq + (df / n) * cast(i as f64));
+        }
+
+        Self {
+            scales,
+            sample_rate,
+        }
+    }
+}
+
+impl_scales!(LinFreqs);
+
+/// Linearly spaced scales, matching fCWT's `FCWT_LINSCALES`.
+///
+/// Scales start at `sample_rate / end_freq` and increase in `size` equal steps
+/// towards `sample_rate / start_freq`, which is excluded.
+#[derive(Debug, Clone)]
+pub struct LinScales<T: Float = f32> {
+    scales: Vec<T>,
+    sample_rate: usize,
+}
+
+impl<T: Float> LinScales<T> {
+    pub fn new(sample_rate: usize, start_freq: T, end_freq: T, size: usize) -> Self {
+        check_freq_range(sample_rate, start_freq, end_freq);
+
+        let fs: T = cast(sample_rate as f64);
+        let s0 = fs / end_freq;
+        let s1 = fs / start_freq;
+
+        // scale delta
+        let ds = s1 - s0;
+        let n: T = cast(size as f64);
+
+        let scales = (0..size).map(|i| s0 + (ds / n) * cast(i as f64)).collect();
+
+        Self {
+            scales,
+            sample_rate,
+        }
+    }
+}
+
+impl_scales!(LinScales);
+
+/// Logarithmically spaced scales, matching fCWT's `FCWT_LOGSCALES`.
+///
+/// Scales are spaced evenly in powers of 2 from `sample_rate / end_freq`
+/// to `sample_rate / start_freq`, both inclusive.
+#[derive(Debug, Clone)]
+pub struct LogScales<T: Float = f32> {
+    scales: Vec<T>,
+    sample_rate: usize,
+}
+
+impl<T: Float> LogScales<T> {
+    pub fn new(sample_rate: usize, start_freq: T, end_freq: T, size: usize) -> Self {
+        check_freq_range(sample_rate, start_freq, end_freq);
+        assert!(start_freq > T::zero(), "start frequency must be positive");
+
+        let base: T = cast(2.0);
+        let fs: T = cast(sample_rate as f64);
+        let s0 = fs / end_freq;
+        let s1 = fs / start_freq;
+
+        let power0 = s0.ln() / base.ln();
+        let power1 = s1.ln() / base.ln();
+        let dpower = power1 - power0;
+        let step = if size > 1 {
+            dpower / cast((size - 1) as f64)
+        } else {
+            T::zero()
+        };
+
+        let scales = (0..size)
+            .map(|i| base.powf(power0 + step * cast(i as f64)))
+            .collect();
+
+        Self {
+            scales,
+            sample_rate,
+        }
+    }
+}
+
+impl_scales!(LogScales);
+
+/// Logarithmically spaced frequencies from `start_freq` to `end_freq`, both inclusive.
+///
+/// Frequencies are ordered from highest to lowest, so scales increase with the index
+/// like the other [`Scales`] implementations.
+#[derive(Debug, Clone)]
+pub struct LogFreqs<T: Float = f32> {
+    scales: Vec<T>,
+    sample_rate: usize,
+}
+
+impl<T: Float> LogFreqs<T> {
+    pub fn new(sample_rate: usize, start_freq: T, end_freq: T, size: usize) -> Self {
+        check_freq_range(sample_rate, start_freq, end_freq);
+        assert!(start_freq > T::zero(), "start frequency must be positive");
+
+        let fs: T = cast(sample_rate as f64);
+        let ratio = (end_freq / start_freq).ln();
+
+        let mut scales: Vec<T> = vec![T::zero(); size];
 
         for i in 0..size {
-            scales[size - i - 1] =
-                (sample_rate as Float) / (start_freq + (df / size as Float) * i as Float);
+            let fraction = if size > 1 {
+                cast::<T>(i as f64) / cast((size - 1) as f64)
+            } else {
+                T::zero()
+            };
+            scales[size - i - 1] = fs / (start_freq * (ratio * fraction).exp());
         }
 
         Self {
@@ -39,37 +202,110 @@ impl LinFreqs {
     }
 }
 
-impl Scales for LinFreqs {
+impl_scales!(LogFreqs);
+
+/// Scales covering whole octaves with a fixed number of voices per octave, as in MATLAB's `cwt`.
+///
+/// Frequencies are `min_freq * 2^(octave + voice / voices_per_octave)`, ordered from
+/// highest to lowest so scales increase with the index like the other [`Scales`]
+/// implementations. The octave and voice of each scale can be used to label axes.
+#[derive(Debug, Clone)]
+pub struct OctaveScales<T: Float = f32> {
+    scales: Vec<T>,
+    sample_rate: usize,
+    voices_per_octave: usize,
+}
+
+impl<T: Float> OctaveScales<T> {
+    /// Create scales spanning `octaves` octaves upwards from `min_freq`, including both ends
+    pub fn new(sample_rate: usize, min_freq: T, octaves: usize, voices_per_octave: usize) -> Self {
+        assert!(octaves > 0, "at least one octave is required");
+        Self::with_steps(
+            sample_rate,
+            min_freq,
+            octaves * voices_per_octave,
+            voices_per_octave,
+        )
+    }
+
+    /// Create scales from `min_freq` up to the highest voice not above `max_freq`
+    pub fn from_range(
+        sample_rate: usize,
+        min_freq: T,
+        max_freq: T,
+        voices_per_octave: usize,
+    ) -> Self {
+        check_freq_range(sample_rate, min_freq, max_freq);
+        assert!(min_freq > T::zero(), "minimum frequency must be positive");
+        assert!(
+            voices_per_octave > 0,
+            "at least one voice per octave is required"
+        );
+
+        // Tolerate rounding when max_freq lies exactly on a voice
+        let steps = ((max_freq / min_freq).log2() * cast(voices_per_octave as f64) + cast(1e-6))
+            .floor()
+            .to_usize()
+            .unwrap();
+
+        Self::with_steps(sample_rate, min_freq, steps, voices_per_octave)
+    }
+
+    fn with_steps(sample_rate: usize, min_freq: T, steps: usize, voices_per_octave: usize) -> Self {
+        assert!(min_freq > T::zero(), "minimum frequency must be positive");
+        assert!(
+            voices_per_octave > 0,
+            "at least one voice per octave is required"
+        );
+
+        let fs: T = cast(sample_rate as f64);
+        let voices: T = cast(voices_per_octave as f64);
+        let freq = |step: usize| min_freq * cast::<T>(2.0).powf(cast::<T>(step as f64) / voices);
+
+        // Ensure the highest voice is below Nyquist frequency (sample rate/2)
+        assert!(freq(steps) <= cast((sample_rate / 2) as f64));
+
+        let scales = (0..=steps).rev().map(|step| fs / freq(step)).collect();
+
+        Self {
+            scales,
+            sample_rate,
+            voices_per_octave,
+        }
+    }
+
     #[inline(always)]
-    fn len(&self) -> usize {
-        self.scales.len()
+    pub fn voices_per_octave(&self) -> usize {
+        self.voices_per_octave
     }
 
+    /// Number of voices above the minimum frequency for a scale index
     #[inline(always)]
-    fn sample_rate(&self) -> usize {
-        self.sample_rate
+    fn step(&self, index: usize) -> usize {
+        assert!(index < self.scales.len(), "Scale index must be in bounds");
+        self.scales.len() - 1 - index
     }
 
+    /// Octave of a scale index, counted from the minimum frequency
     #[inline(always)]
-    fn scale(&self, index: usize) -> Float {
-        self.scales[index]
+    pub fn octave(&self, index: usize) -> usize {
+        self.step(index) / self.voices_per_octave
     }
 
+    /// Voice of a scale index within its octave
     #[inline(always)]
-    fn freq(&self, index: usize) -> Float {
-        assert!(
-            index < self.scales.len(),
-            "Frequency Index must be in bounds"
-        );
-        self.sample_rate as Float / self.scales[index]
+    pub fn voice(&self, index: usize) -> usize {
+        self.step(index) % self.voices_per_octave
     }
 }
 
+impl_scales!(OctaveScales);
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
-    const EPS: Float = 1e-5;
+    const EPS: f32 = 1e-5;
 
     #[test]
     fn test_lin_freqs_new() {
@@ -77,7 +313,7 @@ mod tests {
         let start_freq = 10.0;
         let end_freq = 20.0;
         let size = 5;
-        let lin_freqs = LinFreqs::new(sample_rate, start_freq, end_freq, size);
+        let lin_freqs = LinFreqs::<f32>::new(sample_rate, start_freq, end_freq, size);
 
         assert_eq!(lin_freqs.scales.len(), size);
         assert_eq!(lin_freqs.sample_rate, sample_rate);
@@ -86,7 +322,7 @@ mod tests {
         let df = end_freq - start_freq;
         for i in 0..size {
             let expected_scale =
-                (sample_rate as Float) / (start_freq + (df / size as Float) * i as Float);
+                (sample_rate as f32) / (start_freq + (df / size as f32) * i as f32);
             assert_eq!(lin_freqs.scales[size - i - 1], expected_scale);
         }
     }
@@ -98,12 +334,12 @@ mod tests {
         let start_freq = 10.0;
         let end_freq = 60.0; // Above Nyquist frequency (sample_rate / 2)
         let size = 5;
-        let _ = LinFreqs::new(sample_rate, start_freq, end_freq, size);
+        let _ = LinFreqs::<f32>::new(sample_rate, start_freq, end_freq, size);
     }
 
     #[test]
     fn test_values() {
-        let scales = LinFreqs::new(1000, 1.0, 20.0, 1000);
+        let scales = LinFreqs::<f32>::new(1000, 1.0, 20.0, 1000);
         assert_eq!(scales.len(), 1000);
 
         // Compare with values from python bindings to fCWT
@@ -111,4 +347,169 @@ mod tests {
         assert!(scales.freq(1) - 19.962 < EPS);
         assert_eq!(scales.freq(scales.len() - 1), 1.0);
     }
+
+    #[test]
+    fn test_lin_scales_new() {
+        let sample_rate = 100;
+        let start_freq: f32 = 10.0;
+        let end_freq: f32 = 20.0;
+        let size = 5;
+        let lin_scales = LinScales::new(sample_rate, start_freq, end_freq, size);
+
+        assert_eq!(lin_scales.scales.len(), size);
+        assert_eq!(lin_scales.sample_rate, sample_rate);
+
+        // Check scales are calculated in the same order of operations as fCWT
+        let s0 = sample_rate as f32 / end_freq;
+        let ds = sample_rate as f32 / start_freq - s0;
+        for i in 0..size {
+            assert_eq!(lin_scales.scales[i], s0 + (ds / size as f32) * i as f32);
+        }
+    }
+
+    #[test]
+    fn test_lin_scales_values() {
+        let scales = LinScales::<f32>::new(1000, 1.0, 20.0, 1000);
+        assert_eq!(scales.len(), 1000);
+
+        // Compare with values from the fCWT FCWT_LINSCALES calculation
+        assert_eq!(scales.scale(0), 50.0);
+        assert_eq!(scales.scale(500), 525.0);
+        assert!((scales.scale(1) - 50.95).abs() < EPS);
+        assert!((scales.scale(999) - 999.05).abs() < 1e-3);
+
+        assert_eq!(scales.freq(0), 20.0);
+        assert!((scales.freq(1) - 19.627_085).abs() < EPS);
+        assert!((scales.freq(2) - 19.267_822).abs() < EPS);
+        assert!((scales.freq(500) - 1.904_762).abs() < EPS);
+        assert!((scales.freq(999) - 1.000_951).abs() < EPS);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_lin_scales_end_freq_above_nyquist() {
+        let _ = LinScales::<f32>::new(100, 10.0, 60.0, 5);
+    }
+
+    #[test]
+    fn test_log_scales_values() {
+        let scales = LogScales::<f32>::new(1000, 1.0, 20.0, 1000);
+        assert_eq!(scales.len(), 1000);
+
+        // Compare with values from the fCWT FCWT_LOGSCALES calculation, base 2
+        assert!((scales.scale(0) - 50.0).abs() < 1e-3);
+        assert!((scales.scale(1) - 50.150_16).abs() < 1e-3);
+        assert!((scales.scale(500) - 223.942_32).abs() < 1e-2);
+        assert!((scales.scale(999) - 1000.0).abs() < 1e-2);
+
+        assert!((scales.freq(0) - 20.0).abs() < EPS);
+        assert!((scales.freq(1) - 19.940_115).abs() < EPS);
+        assert!((scales.freq(2) - 19.880_41).abs() < EPS);
+        assert!((scales.freq(500) - 4.465_435_6).abs() < EPS);
+        assert!((scales.freq(998) - 1.003_003_2).abs() < EPS);
+        assert!((scales.freq(999) - 1.0).abs() < EPS);
+    }
+
+    #[test]
+    fn test_log_freqs_values() {
+        let freqs = LogFreqs::<f64>::new(1000, 1.0, 20.0, 1000);
+        let scales = LogScales::<f64>::new(1000, 1.0, 20.0, 1000);
+        assert_eq!(freqs.len(), 1000);
+
+        // Both span the same log-spaced grid, ordered from the highest frequency
+        for i in 0..freqs.len() {
+            assert!((freqs.freq(i) - scales.freq(i)).abs() < 1e-9);
+        }
+        assert!((freqs.freq(0) - 20.0).abs() < 1e-9);
+        assert!((freqs.freq(999) - 1.0).abs() < 1e-9);
+
+        // Constant ratio between neighbouring frequencies
+        let ratio = freqs.freq(0) / freqs.freq(1);
+        for i in 1..freqs.len() {
+            assert!((freqs.freq(i - 1) / freqs.freq(i) - ratio).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_log_scales_end_freq_above_nyquist() {
+        let _ = LogScales::<f32>::new(100, 10.0, 60.0, 5);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_log_freqs_end_freq_above_nyquist() {
+        let _ = LogFreqs::<f32>::new(100, 10.0, 60.0, 5);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_log_freqs_zero_start() {
+        let _ = LogFreqs::<f32>::new(100, 0.0, 20.0, 5);
+    }
+
+    #[test]
+    fn test_octave_scales_new() {
+        let scales = OctaveScales::<f64>::new(1000, 10.0, 3, 4);
+
+        // 3 octaves of 4 voices, including the top of the last octave
+        assert_eq!(scales.len(), 13);
+        assert_eq!(scales.voices_per_octave(), 4);
+        assert!((scales.freq(0) - 80.0).abs() < 1e-9);
+        assert!((scales.freq(12) - 10.0).abs() < 1e-9);
+
+        assert_eq!((scales.octave(0), scales.voice(0)), (3, 0));
+        assert_eq!((scales.octave(1), scales.voice(1)), (2, 3));
+        assert_eq!((scales.octave(4), scales.voice(4)), (2, 0));
+        assert_eq!((scales.octave(12), scales.voice(12)), (0, 0));
+
+        for i in 0..scales.len() {
+            let expected = 10.0 * 2f64.powf(scales.octave(i) as f64 + scales.voice(i) as f64 / 4.0);
+            assert!((scales.freq(i) - expected).abs() < 1e-9);
+            assert!((scales.scale(i) - 1000.0 / expected).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn test_octave_scales_from_range() {
+        let scales = OctaveScales::<f32>::from_range(1000, 10.0, 100.0, 12);
+
+        // log2(10) * 12 = 39.86, so 39 voices above the minimum frequency
+        assert_eq!(scales.len(), 40);
+        assert!(scales.freq(0) <= 100.0);
+        assert!((scales.freq(39) - 10.0).abs() < EPS);
+        assert_eq!((scales.octave(0), scales.voice(0)), (3, 3));
+
+        // An upper frequency exactly on a voice is included
+        let scales = OctaveScales::<f32>::from_range(1000, 10.0, 80.0, 2);
+        assert_eq!(scales.len(), 7);
+        assert!((scales.freq(0) - 80.0).abs() < 1e-4);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_octave_scales_above_nyquist() {
+        let _ = OctaveScales::<f32>::new(100, 10.0, 3, 4);
+    }
+
+    #[test]
+    fn test_values_f64() {
+        let scales = LinFreqs::<f64>::new(1000, 1.0, 20.0, 1000);
+        let single = LinFreqs::<f32>::new(1000, 1.0, 20.0, 1000);
+
+        for i in 0..scales.len() {
+            assert!((scales.freq(i) - single.freq(i) as f64).abs() < 1e-4);
+        }
+        assert_eq!(scales.freq(scales.len() - 1), 1.0);
+    }
+
+    #[test]
+    fn test_fourier_freq() {
+        let scales = LinFreqs::<f64>::new(1000, 10.0, 100.0, 10);
+        let morlet = crate::MorletWavelet::new(1.0);
+
+        for i in 0..scales.len() {
+            assert_eq!(scales.fourier_freq(i, &morlet), scales.freq(i) / morlet.four_wavelen());
+        }
+    }
 }
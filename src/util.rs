@@ -1,11 +1,14 @@
+use crate::{cast, Float};
+
 /// Generate a linear chirp
-pub fn chirp(fs: f32, n_samples: usize, start_freq: f32, end_freq: f32) -> Vec<f32> {
+pub fn chirp<T: Float>(fs: T, n_samples: usize, start_freq: T, end_freq: T) -> Vec<T> {
     let mut signal = Vec::with_capacity(n_samples);
 
     for i in 0..n_samples {
-        let t = i as f32 / fs;
-        let k = (end_freq - start_freq) / n_samples as f32;
-        signal.push((2.0 * std::f32::consts::PI * (start_freq + k * i as f32) * t).sin());
+        let i: T = cast(i as f64);
+        let t = i / fs;
+        let k = (end_freq - start_freq) / cast(n_samples as f64);
+        signal.push((cast::<T>(2.0) * T::PI() * (start_freq + k * i) * t).sin());
     }
 
     signal
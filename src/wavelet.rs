@@ -1,180 +1,864 @@
-use std::f32::consts::PI;
-
-pub type Float = super::Float;
-pub type Complex = super::Complex;
-
-pub trait Wavelet {
-    fn generate_mother(&mut self, size: usize) -> Vec<Float>;
-    fn generate(&self, size: usize, scale: Float) -> Vec<Complex>;
-    //fn get(&self, size: usize, scale: Float) -> Vec<Complex>;
-    fn bandwidth(&self) -> Float;
-    fn four_wavelen(&self) -> Float;
-    fn imag_frequency(&self) -> bool;
-    fn is_double_sided(&self) -> bool;
-    fn mother(&self) -> &[Float];
-
-    fn get_support(&self, scale: Float) -> isize {
-        (self.bandwidth() * scale * 3.0) as isize
-    }
-}
-
-pub struct MorletWavelet {
-    four_wavelen: Float,
-    imag_frequency: bool,
-    double_sided: bool,
-    mother: Vec<Float>,
-
-    fb: Float,
-    ifb: Float,
-    fb2: Float,
-}
-
-impl MorletWavelet {
-    pub fn new(bandwidth: Float) -> Self {
-        Self {
-            four_wavelen: 0.9876,
-            fb: bandwidth,
-            fb2: 2.0 * bandwidth * bandwidth,
-            ifb: 1.0 / bandwidth,
-            imag_frequency: false,
-            double_sided: false,
-            mother: vec![],
-        }
-    }
-}
-
-impl Wavelet for MorletWavelet {
-    fn generate_mother(&mut self, size: usize) -> Vec<Float> {
-        let mut mother = Vec::with_capacity(size);
-
-        let torad = (2.0 * PI) / size as Float;
-        let norm = (2.0 * PI).sqrt() * super::IPI4;
-
-        for i in 0..size {
-            //let mut tmp = 2.0 * (i as Float).to_radians() * self.fb - 2.0 * PI * self.fb;
-            let mut tmp = 2.0 * (i as Float * torad) * self.fb - 2.0 * PI * self.fb;
-            tmp = -(tmp * tmp) / 2.0;
-
-            mother.push(norm * tmp.exp());
-        }
-
-        self.mother = mother.clone();
-
-        mother
-    }
-
-    fn generate(&self, size: usize, scale: Float) -> Vec<Complex> {
-        let width = self.get_support(scale);
-        let norm = size as Float * self.ifb * super::IPI4;
-
-        let mut output: Vec<Complex> = Vec::with_capacity((width * 2 + 1) as usize);
-
-        for i in 0..width * 2 + 1 {
-            let tmp1 = (i - width) as Float / scale;
-            let tmp2 = (-(tmp1 * tmp1) / self.fb2).exp();
-
-            let real = norm * tmp2 * (tmp1 * 2.0 * PI).cos() / scale;
-            let imag = norm * tmp2 * (tmp1 * 2.0 * PI).sin() / scale;
-
-            output.push(Complex::new(real, imag));
-        }
-
-        output
-    }
-
-    #[inline(always)]
-    fn bandwidth(&self) -> Float {
-        self.fb
-    }
-
-    #[inline(always)]
-    fn four_wavelen(&self) -> Float {
-        self.four_wavelen
-    }
-
-    #[inline(always)]
-    fn imag_frequency(&self) -> bool {
-        self.imag_frequency
-    }
-
-    #[inline(always)]
-    fn is_double_sided(&self) -> bool {
-        self.double_sided
-    }
-
-    #[inline(always)]
-    fn mother(&self) -> &[Float] {
-        self.mother.as_slice()
-    }
-}
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-
-    // Epsilon for float comparison
-    const EPS: Float = 1e-5;
-
-    #[test]
-    fn test_morlet_wavelet_new() {
-        let morlet = MorletWavelet::new(1.0);
-        assert_eq!(morlet.fb, 1.0);
-        assert_eq!(morlet.fb2, 2.0);
-        assert_eq!(morlet.ifb, 1.0);
-        assert_eq!(morlet.four_wavelen, 0.9876);
-        assert!(!morlet.imag_frequency);
-        assert!(!morlet.double_sided);
-    }
-
-    #[test]
-    fn test_morlet_wavelet_generate_mother() {
-        let mut morlet = MorletWavelet::new(1.0);
-        morlet.mother = morlet.generate_mother(4);
-        assert_eq!(morlet.mother.len(), 4);
-    }
-
-    #[test]
-    fn test_morlet_wavelet_generate_time() {
-        let morlet = MorletWavelet::new(2.0);
-        let size = 25;
-        let scale = 2.0;
-        let result = morlet.generate(size, scale);
-
-        assert_eq!(
-            result.len(),
-            (morlet.get_support(scale) as Float * 2.0 + 1.0) as usize
-        );
-
-        // Check if values we computed are within epsilon of values
-        // produced using python bindings to fCWT C++ library
-        assert!(result[0].re - 0.05215157 < EPS);
-        assert!(result[0].im - -4.9752100e-09 < EPS);
-        assert!(result[1].re - -0.10700808 < EPS);
-        assert!(result[1].im - 2.8149339e-07 < EPS);
-
-        for (i, complex) in result.iter().enumerate() {
-            let tmp1 = (i as isize - morlet.get_support(scale)) as f32 / scale;
-            let tmp2 = (-(tmp1 * tmp1) / morlet.fb2).exp();
-            let expected_real =
-                size as Float * morlet.ifb * crate::IPI4 * tmp2 * (tmp1 * 2.0 * PI).cos() / scale;
-            let expected_imag =
-                size as Float * morlet.ifb * crate::IPI4 * tmp2 * (tmp1 * 2.0 * PI).sin() / scale;
-
-            // Check if within epsilon
-            assert!((complex.re - expected_real).abs() < EPS);
-            assert!((complex.im - expected_imag).abs() < EPS);
-        }
-    }
-
-    #[test]
-    fn test_morlet_wavelet_bandwidth() {
-        let morlet = MorletWavelet::new(2.5);
-        assert_eq!(morlet.bandwidth(), 2.5);
-    }
-
-    #[test]
-    fn test_morlet_wavelet_get_support() {
-        let morlet = MorletWavelet::new(1.0);
-        assert_eq!(morlet.get_support(1.0), 3);
-    }
-}
+use std::ops::Range;
+use std::sync::{Arc, OnceLock};
+
+use crate::fft::FftPlanner;
+use crate::{cast, Complex, Float};
+
+mod bump;
+mod custom;
+mod dog;
+mod fbsp;
+mod morse;
+mod paul;
+mod shannon;
+
+pub use bump::BumpWavelet;
+pub use custom::CustomWavelet;
+pub use dog::DogWavelet;
+pub use fbsp::FbspWavelet;
+pub use morse::MorseWavelet;
+pub use paul::PaulWavelet;
+pub use shannon::ShannonWavelet;
+
+/// A wavelet defined by its frequency response.
+///
+/// The mother wavelet is sampled in the frequency domain on a normalized axis `u`, where
+/// sample `j` of a mother of size `n` lies at `u = 2j/n` and the response peaks at `u = 1`.
+/// The daughter at scale `s` then peaks at `1/s` cycles per sample, so a scale corresponds
+/// to the frequency `sample_rate / s`.
+pub trait Wavelet<T: Float = f32> {
+    fn generate_mother(&mut self, size: usize) -> Vec<T>;
+    fn generate(&self, size: usize, scale: T) -> Vec<Complex<T>>;
+    //fn get(&self, size: usize, scale: T) -> Vec<Complex<T>>;
+    fn bandwidth(&self) -> T;
+
+    /// Ratio of the equivalent Fourier period (Torrence & Compo, 1998) to the peak period,
+    /// the inverse of the `u` where `u * |response(u)|^2` peaks
+    fn four_wavelen(&self) -> T {
+        cast(numeric_four_wavelen(self))
+    }
+
+    /// Whether the frequency response is `i * mother` rather than `mother`
+    fn imag_frequency(&self) -> bool;
+    /// Whether the wavelet is real valued, with a response at negative frequencies that is
+    /// the conjugate of the response at the matching positive frequency
+    fn is_double_sided(&self) -> bool;
+    fn mother(&self) -> &[T];
+
+    fn get_support(&self, scale: T) -> isize {
+        (self.bandwidth() * scale * cast(3.0)).to_isize().unwrap()
+    }
+
+    /// Range of `u` outside which the frequency response is exactly zero, for wavelets
+    /// with compact support in frequency. The transform skips the bins outside it.
+    fn frequency_support(&self) -> Option<Range<T>> {
+        None
+    }
+
+    /// Mother wavelet response at an arbitrary `u`, the value [`Wavelet::generate_mother`]
+    /// samples on its grid. Wavelets without a closed form response return `None`, and
+    /// the transform interpolates between mother samples instead.
+    fn frequency_response(&self, _u: T) -> Option<T> {
+        None
+    }
+
+    /// Plan any FFTs the wavelet runs itself with `planner`. [`crate::FastCwt`] passes its
+    /// planner on whenever it is set, so the backend choice covers the wavelet as well.
+    fn set_planner(&mut self, _planner: Arc<dyn FftPlanner<T>>) {}
+
+    /// Cache for the metadata defaults below, which integrate the sampled response
+    /// numerically. Without one they are recomputed on every call. Only wavelets whose
+    /// response never changes after construction should return one.
+    fn metadata_cache(&self) -> Option<&MetadataCache> {
+        None
+    }
+
+    /// Center frequency relative to the peak frequency, the mean `u` of the energy spectrum
+    /// `|response(u)|^2` over positive frequencies
+    fn center_frequency(&self) -> T {
+        cast(response_moments(self).0)
+    }
+
+    /// Frequency spread relative to the peak frequency, the standard deviation of `u` under
+    /// the energy spectrum `|response(u)|^2` over positive frequencies
+    fn frequency_spread(&self) -> T {
+        cast(response_moments(self).1)
+    }
+
+    /// Time spread in units of the peak period, the standard deviation of time under
+    /// `|wavelet(t)|^2`.
+    ///
+    /// The response is real, so the wavelet is centered on zero and its spread follows from
+    /// the derivative of the response, `int |response'(u)|^2 du / (4 pi^2 int |response(u)|^2 du)`.
+    fn time_spread(&self) -> T {
+        cast(numeric_time_spread(self))
+    }
+
+    /// Time and frequency spread of the daughter wavelet at `scale`, in samples and cycles
+    /// per sample. The area of the box is bounded below by `1 / 4pi`.
+    fn heisenberg_box(&self, scale: T) -> (T, T) {
+        (self.time_spread() * scale, self.frequency_spread() / scale)
+    }
+
+    /// E-folding time in units of the peak period, where the envelope of the wavelet drops to
+    /// `1/e` of its peak and the power of a spike to `e^-2` (Torrence & Compo, 1998). Edge
+    /// effects dominate the transform within this time of either end of the signal.
+    fn efolding_time(&self) -> T {
+        cast(numeric_efolding_time(self))
+    }
+
+    /// Admissibility constant `int |response(u)|^2 / |u| du`, over both sides of the
+    /// response for double sided wavelets. It is finite only if the response vanishes at
+    /// `u = 0`, and the wavelet can then be inverted with the double integral formula.
+    fn admissibility_constant(&self) -> T {
+        cast(numeric_admissibility_constant(self))
+    }
+
+    /// Reconstruction constant used by the inverse transform, the integral of
+    /// `mother(u) / u` over the generated mother wavelet.
+    ///
+    /// Mother sample `j` of `n` lies at `u = 2j/n`, so the integral reduces to the
+    /// sum of `mother[j] / j`. The mother must have been generated first.
+    fn reconstruction_constant(&self) -> T {
+        let mother = self.mother();
+        assert!(!mother.is_empty(), "mother wavelet must be generated");
+
+        mother
+            .iter()
+            .enumerate()
+            .skip(1)
+            .fold(T::zero(), |acc, (j, &value)| acc + value / cast(j as f64))
+    }
+}
+
+/// Length of a mother wavelet of `size` that covers the response up to `u = cutoff`.
+///
+/// Broadband or slowly decaying responses, like the derivative of Gaussian and Paul
+/// wavelets, are still significant past `u = 2`. Their mother extends past `size` up to
+/// where the response becomes negligible, so that small scales don't truncate the response.
+pub fn mother_len(size: usize, cutoff: f64) -> usize {
+    ((size as f64 * cutoff / 2.0).ceil() as usize).max(size)
+}
+
+/// Lowest `u` above the peak at `u = 1` where `response` drops below 1e-6 of the peak
+pub(crate) fn frequency_cutoff(response: impl Fn(f64) -> f64) -> f64 {
+    let peak = response(1.0).abs();
+    let mut u = 2.0;
+
+    // Responses that never decay are cut off at a fixed limit
+    while u < 16.0 && response(u).abs() >= 1e-6 * peak {
+        u += 0.5;
+    }
+    u
+}
+
+/// Inverse Fourier transform at `t` of a frequency `response` that is negligible outside
+/// `lower..upper`, integrated numerically with the midpoint rule
+pub(crate) fn time_response(
+    response: impl Fn(f64) -> f64,
+    lower: f64,
+    upper: f64,
+    t: f64,
+) -> Complex<f64> {
+    let steps = ((upper - lower) * 64.0 * (1.0 + t.abs())).ceil() as usize + 64;
+    let du = (upper - lower) / steps as f64;
+
+    (0..steps)
+        .map(|j| {
+            let u = lower + (j as f64 + 0.5) * du;
+            Complex::from_polar(response(u) * du, std::f64::consts::TAU * u * t)
+        })
+        .sum()
+}
+
+/// Time from `min` onwards after which `envelope` stays below 1e-3 of its value at zero.
+///
+/// The envelope may pass through zero, so it has to stay below the threshold for a full
+/// peak period.
+pub(crate) fn time_support(envelope: impl Fn(f64) -> f64, min: f64) -> f64 {
+    let threshold = 1e-3 * envelope(0.0);
+    let mut support = min;
+    let mut t = min;
+    while t < support + 1.0 {
+        if envelope(t) >= threshold {
+            support = t;
+        }
+        t += 0.05;
+    }
+    support
+}
+
+/// Numeric wavelet metadata, each computed on first use and kept for the wavelet's lifetime.
+/// See [`Wavelet::metadata_cache`].
+#[derive(Debug, Default)]
+pub struct MetadataCache {
+    four_wavelen: OnceLock<f64>,
+    moments: OnceLock<(f64, f64)>,
+    time_spread: OnceLock<f64>,
+    efolding_time: OnceLock<f64>,
+    admissibility_constant: OnceLock<f64>,
+}
+
+/// Get a value from the wavelet's metadata cache, computing it if missing
+fn cached<T: Float, W: Wavelet<T> + ?Sized, V: Copy>(
+    wavelet: &W,
+    field: impl FnOnce(&MetadataCache) -> &OnceLock<V>,
+    compute: impl FnOnce() -> V,
+) -> V {
+    match wavelet.metadata_cache() {
+        Some(cache) => *field(cache).get_or_init(compute),
+        None => compute(),
+    }
+}
+
+/// Mother response at `u = k * du` for a uniform grid of `k`, used for the numeric wavelet
+/// metadata.
+///
+/// The closed form response is sampled up to `u = 16`. Wavelets without one fall back to
+/// the generated mother, whose peak lies at `u = 1`.
+fn sampled_response<T: Float, W: Wavelet<T> + ?Sized>(wavelet: &W) -> (Vec<f64>, f64) {
+    if wavelet.frequency_response(T::one()).is_some() {
+        let du = 1.0 / 1024.0;
+        let response = (0..16 * 1024)
+            .map(|k| {
+                let value = wavelet.frequency_response(cast(k as f64 * du)).unwrap();
+                value.to_f64().unwrap()
+            })
+            .collect();
+        return (response, du);
+    }
+
+    let mother = wavelet.mother();
+    assert!(!mother.is_empty(), "mother wavelet must be generated");
+    let peak = mother
+        .iter()
+        .enumerate()
+        .max_by(|a, b| a.1.abs().partial_cmp(&b.1.abs()).unwrap())
+        .unwrap()
+        .0;
+
+    (
+        mother.iter().map(|value| value.to_f64().unwrap()).collect(),
+        1.0 / peak.max(1) as f64,
+    )
+}
+
+/// Inverse of the `u` where `u * |response(u)|^2` peaks
+fn numeric_four_wavelen<T: Float, W: Wavelet<T> + ?Sized>(wavelet: &W) -> f64 {
+    cached(
+        wavelet,
+        |cache| &cache.four_wavelen,
+        || {
+            let (response, du) = sampled_response(wavelet);
+            let power = |k: usize| k as f64 * response[k] * response[k];
+            let peak = (1..response.len())
+                .max_by(|&a, &b| power(a).total_cmp(&power(b)))
+                .unwrap_or(1);
+
+            1.0 / (peak as f64 * du)
+        },
+    )
+}
+
+/// Mean and standard deviation of `u` under the energy spectrum of the mother response
+fn response_moments<T: Float, W: Wavelet<T> + ?Sized>(wavelet: &W) -> (f64, f64) {
+    cached(
+        wavelet,
+        |cache| &cache.moments,
+        || {
+            let (response, du) = sampled_response(wavelet);
+            let (mut energy, mut mean, mut square) = (0.0, 0.0, 0.0);
+            for (k, value) in response.iter().enumerate() {
+                let (u, power) = (k as f64 * du, value * value);
+                energy += power;
+                mean += u * power;
+                square += u * u * power;
+            }
+
+            let (mean, square) = (mean / energy, square / energy);
+            (mean, (square - mean * mean).max(0.0).sqrt())
+        },
+    )
+}
+
+/// Admissibility constant over the sampled response, see [`Wavelet::admissibility_constant`]
+fn numeric_admissibility_constant<T: Float, W: Wavelet<T> + ?Sized>(wavelet: &W) -> f64 {
+    cached(
+        wavelet,
+        |cache| &cache.admissibility_constant,
+        || {
+            let (response, _) = sampled_response(wavelet);
+            let sides = if wavelet.is_double_sided() { 2.0 } else { 1.0 };
+
+            sides
+                * response
+                    .iter()
+                    .enumerate()
+                    .skip(1)
+                    .map(|(k, value)| value * value / k as f64)
+                    .sum::<f64>()
+        },
+    )
+}
+
+/// Time spread from the derivative of the response, see [`Wavelet::time_spread`]
+fn numeric_time_spread<T: Float, W: Wavelet<T> + ?Sized>(wavelet: &W) -> f64 {
+    cached(
+        wavelet,
+        |cache| &cache.time_spread,
+        || {
+            let (response, du) = sampled_response(wavelet);
+            let energy: f64 = response.iter().map(|value| value * value).sum();
+            let slope: f64 = response
+                .windows(2)
+                .map(|pair| (pair[1] - pair[0]).powi(2))
+                .sum::<f64>()
+                / (du * du);
+
+            (slope / energy).sqrt() / std::f64::consts::TAU
+        },
+    )
+}
+
+/// Time where the envelope of the positive frequency part of the response drops to `1/e`
+fn numeric_efolding_time<T: Float, W: Wavelet<T> + ?Sized>(wavelet: &W) -> f64 {
+    cached(
+        wavelet,
+        |cache| &cache.efolding_time,
+        || {
+            let (response, du) = sampled_response(wavelet);
+            let envelope = |t: f64| {
+                response
+                    .iter()
+                    .enumerate()
+                    .map(|(k, &value)| {
+                        Complex::from_polar(value, std::f64::consts::TAU * k as f64 * du * t)
+                    })
+                    .sum::<Complex<f64>>()
+                    .norm()
+            };
+
+            // Step out to the first crossing, then bisect
+            let threshold = envelope(0.0) / std::f64::consts::E;
+            let step = 0.05;
+            let mut upper = step;
+            while upper < 64.0 && envelope(upper) > threshold {
+                upper += step;
+            }
+            let mut lower = upper - step;
+            for _ in 0..32 {
+                let t = (lower + upper) / 2.0;
+                if envelope(t) > threshold {
+                    lower = t;
+                } else {
+                    upper = t;
+                }
+            }
+
+            (lower + upper) / 2.0
+        },
+    )
+}
+
+/// Complex Morlet wavelet, a Gaussian in frequency centered on the peak.
+///
+/// The bandwidth `fb` is the standard deviation of the time envelope in peak periods,
+/// which corresponds to a center frequency `omega0 = 2 pi fb` of the standard form
+/// `e^(i omega0 t) e^(-t^2 / 2)`. Small `omega0` leave a response at zero frequency, which
+/// [`MorletWavelet::with_correction`] removes to make the wavelet exactly admissible.
+pub struct MorletWavelet<T: Float = f32> {
+    four_wavelen: T,
+    imag_frequency: bool,
+    double_sided: bool,
+    mother: Vec<T>,
+
+    fb: T,
+    ifb: T,
+    fb2: T,
+    kappa: T,
+    metadata: MetadataCache,
+}
+
+impl<T: Float> MorletWavelet<T> {
+    pub fn new(bandwidth: T) -> Self {
+        // Torrence & Compo Fourier wavelength 4 pi / (omega0 + sqrt(2 + omega0^2)),
+        // relative to the peak period 2 pi / omega0
+        let omega0 = T::TAU() * bandwidth;
+        let four_wavelen =
+            cast::<T>(2.0) * omega0 / (omega0 + (cast::<T>(2.0) + omega0 * omega0).sqrt());
+
+        Self {
+            four_wavelen,
+            fb: bandwidth,
+            fb2: cast::<T>(2.0) * bandwidth * bandwidth,
+            ifb: T::one() / bandwidth,
+            kappa: T::zero(),
+            imag_frequency: false,
+            double_sided: false,
+            mother: vec![],
+            metadata: MetadataCache::default(),
+        }
+    }
+
+    /// Morlet wavelet `e^(i omega0 t) e^(-t^2 / 2)` with center frequency `omega0`, in
+    /// radians per unit time
+    pub fn with_omega0(omega0: T) -> Self {
+        Self::new(omega0 / T::TAU())
+    }
+
+    /// Morlet wavelet matching PyWavelets' `cmorB-C`, `e^(2 pi i C t) e^(-t^2 / B)`, with
+    /// bandwidth `B` and center frequency `C`
+    pub fn cmor(bandwidth: T, center_frequency: T) -> Self {
+        Self::new(center_frequency * (bandwidth / cast(2.0)).sqrt())
+    }
+
+    /// Subtract `kappa e^(-t^2 / 2)`, with `kappa = e^(-omega0^2 / 2)`, so that the response
+    /// vanishes at zero frequency. Negligible for `omega0` above about 5.
+    pub fn with_correction(mut self, correct: bool) -> Self {
+        self.kappa = if correct {
+            let omega0 = self.omega0();
+            (-(omega0 * omega0) / cast(2.0)).exp()
+        } else {
+            T::zero()
+        };
+        self.metadata = MetadataCache::default();
+        self
+    }
+
+    /// Center frequency `omega0 = 2 pi fb` of the standard form, in radians per unit time
+    #[inline]
+    pub fn omega0(&self) -> T {
+        T::TAU() * self.fb
+    }
+
+    /// Whether the admissibility correction is applied
+    #[inline]
+    pub fn is_corrected(&self) -> bool {
+        !self.kappa.is_zero()
+    }
+}
+
+impl<T: Float> Wavelet<T> for MorletWavelet<T> {
+    fn generate_mother(&mut self, size: usize) -> Vec<T> {
+        let mut mother = Vec::with_capacity(size);
+
+        let two = cast::<T>(2.0);
+        let two_pi = T::TAU();
+        let torad = two_pi / cast(size as f64);
+        let norm = two_pi.sqrt() * cast(super::IPI4);
+
+        for i in 0..size {
+            //let mut tmp = 2.0 * (i as T).to_radians() * self.fb - 2.0 * PI * self.fb;
+            let offset = two * (cast::<T>(i as f64) * torad) * self.fb;
+            let mut tmp = offset - two_pi * self.fb;
+            tmp = -(tmp * tmp) / two;
+
+            if self.is_corrected() {
+                mother.push(norm * (tmp.exp() - self.kappa * (-(offset * offset) / two).exp()));
+            } else {
+                mother.push(norm * tmp.exp());
+            }
+        }
+
+        self.mother = mother.clone();
+
+        mother
+    }
+
+    fn generate(&self, size: usize, scale: T) -> Vec<Complex<T>> {
+        let width = self.get_support(scale);
+        let norm = cast::<T>(size as f64) * self.ifb * cast(super::IPI4);
+
+        let mut output: Vec<Complex<T>> = Vec::with_capacity((width * 2 + 1) as usize);
+
+        for i in 0..width * 2 + 1 {
+            let tmp1 = cast::<T>((i - width) as f64) / scale;
+            let tmp2 = (-(tmp1 * tmp1) / self.fb2).exp();
+
+            let real = norm * tmp2 * ((tmp1 * cast(2.0) * T::PI()).cos() - self.kappa) / scale;
+            let imag = norm * tmp2 * (tmp1 * cast(2.0) * T::PI()).sin() / scale;
+
+            output.push(Complex::new(real, imag));
+        }
+
+        output
+    }
+
+    #[inline(always)]
+    fn bandwidth(&self) -> T {
+        self.fb
+    }
+
+    #[inline(always)]
+    fn four_wavelen(&self) -> T {
+        if self.is_corrected() {
+            cast(numeric_four_wavelen(self))
+        } else {
+            self.four_wavelen
+        }
+    }
+
+    #[inline(always)]
+    fn imag_frequency(&self) -> bool {
+        self.imag_frequency
+    }
+
+    #[inline(always)]
+    fn is_double_sided(&self) -> bool {
+        self.double_sided
+    }
+
+    #[inline(always)]
+    fn mother(&self) -> &[T] {
+        self.mother.as_slice()
+    }
+
+    /// The response is a Gaussian centered on the peak, unless corrected
+    fn center_frequency(&self) -> T {
+        if self.is_corrected() {
+            cast(response_moments(self).0)
+        } else {
+            T::one()
+        }
+    }
+
+    fn frequency_spread(&self) -> T {
+        if self.is_corrected() {
+            cast(response_moments(self).1)
+        } else {
+            T::one() / (T::TAU() * T::SQRT_2() * self.fb)
+        }
+    }
+
+    /// The envelope is `e^(-t^2 / (2 fb^2))`
+    fn time_spread(&self) -> T {
+        if self.is_corrected() {
+            cast(numeric_time_spread(self))
+        } else {
+            self.fb / T::SQRT_2()
+        }
+    }
+
+    fn efolding_time(&self) -> T {
+        if self.is_corrected() {
+            cast(numeric_efolding_time(self))
+        } else {
+            T::SQRT_2() * self.fb
+        }
+    }
+
+    fn frequency_response(&self, u: T) -> Option<T> {
+        let (tmp, offset) = (T::TAU() * self.fb * (u - T::one()), T::TAU() * self.fb * u);
+        let response =
+            (-(tmp * tmp) / cast(2.0)).exp() - self.kappa * (-(offset * offset) / cast(2.0)).exp();
+        Some(T::TAU().sqrt() * cast(super::IPI4) * response)
+    }
+
+    fn metadata_cache(&self) -> Option<&MetadataCache> {
+        Some(&self.metadata)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f32::consts::PI;
+
+    // Epsilon for float comparison
+    const EPS: f32 = 1e-5;
+
+    /// One second of a sine at `freq` Hz, sampled at 1 kHz
+    pub(super) fn sine(freq: f32) -> Vec<f32> {
+        (0..1000)
+            .map(|i| (2.0 * PI * freq * i as f32 / 1000.0).sin())
+            .collect()
+    }
+
+    /// Assert that the CWT of a 75 Hz sine peaks within `tolerance` Hz of it, away from the edges
+    pub(super) fn assert_cwt_peak<W: Wavelet<f32> + Sync>(wavelet: W, tolerance: f32) {
+        let scales = crate::LinFreqs::new(1000, 10.0, 200.0, 96);
+        let mut fast_cwt = crate::FastCwt::new(wavelet, scales, true);
+        let result = fast_cwt.cwt(&sine(75.0));
+
+        let peak = result
+            .rows()
+            .map(|row| row[250..750].iter().map(|c| c.norm()).sum::<f32>())
+            .enumerate()
+            .max_by(|a, b| a.1.total_cmp(&b.1))
+            .unwrap()
+            .0;
+
+        let freq = crate::Scales::freq(fast_cwt.scales(), peak);
+        assert!((freq - 75.0).abs() < tolerance, "peak at {freq} Hz");
+    }
+
+    #[test]
+    fn test_morlet_wavelet_new() {
+        let morlet = MorletWavelet::<f32>::new(1.0);
+        assert_eq!(morlet.fb, 1.0);
+        assert_eq!(morlet.fb2, 2.0);
+        assert_eq!(morlet.ifb, 1.0);
+        assert!((morlet.four_wavelen - 0.9876).abs() < 1e-4);
+        assert_eq!(morlet.kappa, 0.0);
+        assert!(!morlet.imag_frequency);
+        assert!(!morlet.double_sided);
+    }
+
+    #[test]
+    fn test_morlet_wavelet_generate_mother() {
+        let mut morlet = MorletWavelet::<f32>::new(1.0);
+        morlet.mother = morlet.generate_mother(4);
+        assert_eq!(morlet.mother.len(), 4);
+    }
+
+    #[test]
+    // Reference values are kept exactly as printed by fCWT
+    #[allow(clippy::excessive_precision)]
+    fn test_morlet_wavelet_generate_time() {
+        let morlet = MorletWavelet::<f32>::new(2.0);
+        let size = 25;
+        let scale = 2.0;
+        let result = morlet.generate(size, scale);
+
+        assert_eq!(
+            result.len(),
+            (morlet.get_support(scale) as f32 * 2.0 + 1.0) as usize
+        );
+
+        // Check if values we computed are within epsilon of values
+        // produced using python bindings to fCWT C++ library
+        assert!(result[0].re - 0.05215157 < EPS);
+        assert!(result[0].im - -4.9752100e-09 < EPS);
+        assert!(result[1].re - -0.10700808 < EPS);
+        assert!(result[1].im - 2.8149339e-07 < EPS);
+
+        for (i, complex) in result.iter().enumerate() {
+            let tmp1 = (i as isize - morlet.get_support(scale)) as f32 / scale;
+            let tmp2 = (-(tmp1 * tmp1) / morlet.fb2).exp();
+            let expected_real =
+                size as f32 * morlet.ifb * crate::IPI4 as f32 * tmp2 * (tmp1 * 2.0 * PI).cos()
+                    / scale;
+            let expected_imag =
+                size as f32 * morlet.ifb * crate::IPI4 as f32 * tmp2 * (tmp1 * 2.0 * PI).sin()
+                    / scale;
+
+            // Check if within epsilon
+            assert!((complex.re - expected_real).abs() < EPS);
+            assert!((complex.im - expected_imag).abs() < EPS);
+        }
+    }
+
+    #[test]
+    fn test_morlet_wavelet_cwt_peak() {
+        assert_cwt_peak(MorletWavelet::new(1.0), 3.0);
+        assert_cwt_peak(MorletWavelet::new(3.0), 3.0);
+    }
+
+    #[test]
+    fn test_morlet_wavelet_bandwidth() {
+        let morlet = MorletWavelet::<f32>::new(2.5);
+        assert_eq!(morlet.bandwidth(), 2.5);
+    }
+
+    #[test]
+    fn test_morlet_wavelet_get_support() {
+        let morlet = MorletWavelet::<f32>::new(1.0);
+        assert_eq!(morlet.get_support(1.0), 3);
+    }
+
+    #[test]
+    fn test_morlet_wavelet_f64() {
+        let mut single = MorletWavelet::<f32>::new(2.0);
+        let mut double = MorletWavelet::<f64>::new(2.0);
+
+        for (&a, &b) in single
+            .generate_mother(1024)
+            .iter()
+            .zip(double.generate_mother(1024).iter())
+        {
+            assert!((a as f64 - b).abs() < 1e-5);
+        }
+
+        for (a, b) in single
+            .generate(25, 2.0)
+            .iter()
+            .zip(double.generate(25, 2.0))
+        {
+            assert!((a.re as f64 - b.re).abs() < 1e-4);
+            assert!((a.im as f64 - b.im).abs() < 1e-4);
+        }
+    }
+
+    #[test]
+    fn test_morlet_wavelet_constructors() {
+        let morlet = MorletWavelet::<f64>::with_omega0(2.0 * std::f64::consts::PI);
+        assert!((morlet.bandwidth() - 1.0).abs() < 1e-12);
+        assert!((morlet.omega0() - 2.0 * std::f64::consts::PI).abs() < 1e-12);
+        assert!(!morlet.is_corrected());
+
+        // PyWavelets cmorB-C: e^(2 pi i C t) e^(-t^2 / B) / sqrt(pi B)
+        let (b, c) = (1.5, 2.0);
+        let morlet = MorletWavelet::<f64>::cmor(b, c);
+        assert!((morlet.bandwidth() - c * (b / 2.0).sqrt()).abs() < 1e-12);
+
+        let scale = 8.0;
+        let kernel = morlet.generate(64, scale);
+        let width = morlet.get_support(scale);
+        let cmor = |t: f64| {
+            Complex::from_polar(1.0, 2.0 * std::f64::consts::PI * c * t) * (-t * t / b).exp()
+                / (std::f64::consts::PI * b).sqrt()
+        };
+
+        // Same shape up to a constant factor, with the peak period mapped to 1 / C
+        let ratio = kernel[width as usize] / cmor(0.0);
+        for (i, value) in kernel.iter().enumerate() {
+            let t = (i as isize - width) as f64 / scale / c;
+            assert!((value - cmor(t) * ratio).norm() < 1e-9 * ratio.norm());
+        }
+    }
+
+    #[test]
+    fn test_morlet_wavelet_correction() {
+        let omega0 = 2.0;
+        let plain = MorletWavelet::<f64>::with_omega0(omega0);
+        let corrected = MorletWavelet::<f64>::with_omega0(omega0).with_correction(true);
+        assert!(corrected.is_corrected());
+        assert!(!corrected.with_correction(false).is_corrected());
+
+        let mut corrected = MorletWavelet::<f64>::with_omega0(omega0).with_correction(true);
+        let mother = corrected.generate_mother(1024);
+        assert!(mother[0].abs() < 1e-12);
+        assert!(corrected.frequency_response(0.0).unwrap().abs() < 1e-12);
+        assert!(plain.frequency_response(0.0).unwrap() > 0.1);
+
+        // The kernel integrates to zero, up to its truncation at the support
+        let sum = |kernel: Vec<Complex<f64>>| kernel.iter().sum::<Complex<f64>>().norm();
+        let offset = sum(plain.generate(64, 16.0));
+        assert!(offset > 1.0);
+        assert!(sum(corrected.generate(64, 16.0)) < 0.02 * offset);
+
+        // Correcting shifts the energy up in frequency and is negligible for large omega0
+        assert!(corrected.center_frequency() > plain.center_frequency());
+        let large = MorletWavelet::<f64>::with_omega0(6.0).with_correction(true);
+        assert!((large.center_frequency() - 1.0).abs() < 1e-6);
+        assert!(
+            (large.four_wavelen() - MorletWavelet::<f64>::with_omega0(6.0).four_wavelen()).abs()
+                < 1e-3
+        );
+    }
+
+    #[test]
+    fn test_morlet_wavelet_correction_removes_dc() {
+        let input = vec![1.0f64; 1024];
+        let scales = crate::LogScales::new(1000, 5.0, 200.0, 8);
+
+        let wavelet = MorletWavelet::with_omega0(2.0).with_correction(true);
+        let result = crate::FastCwt::new(wavelet, scales.clone(), true).cwt(&input);
+        assert!(result.as_slice().iter().all(|x| x.norm() < 1e-9));
+
+        let wavelet = MorletWavelet::with_omega0(2.0);
+        let result = crate::FastCwt::new(wavelet, scales, true).cwt(&input);
+        assert!(result.as_slice().iter().any(|x| x.norm() > 0.1));
+    }
+
+    /// Morlet wavelet without a closed form response, exercising the numeric defaults
+    struct SampledMorlet(MorletWavelet<f64>);
+
+    impl Wavelet<f64> for SampledMorlet {
+        fn generate_mother(&mut self, size: usize) -> Vec<f64> {
+            self.0.generate_mother(size)
+        }
+        fn generate(&self, size: usize, scale: f64) -> Vec<Complex<f64>> {
+            self.0.generate(size, scale)
+        }
+        fn bandwidth(&self) -> f64 {
+            self.0.bandwidth()
+        }
+        fn imag_frequency(&self) -> bool {
+            false
+        }
+        fn is_double_sided(&self) -> bool {
+            false
+        }
+        fn mother(&self) -> &[f64] {
+            self.0.mother()
+        }
+    }
+
+    #[test]
+    fn test_wavelet_metadata_defaults() {
+        for fb in [0.5, 1.0, 2.0] {
+            let morlet = MorletWavelet::<f64>::new(fb);
+            let mut sampled = SampledMorlet(MorletWavelet::new(fb));
+            sampled.generate_mother(1024);
+
+            // Torrence & Compo Fourier wavelength relative to the peak period, w0 = 2 pi fb
+            let omega0 = std::f64::consts::TAU * fb;
+            let expected = 2.0 * omega0 / (omega0 + (2.0 + omega0 * omega0).sqrt());
+            assert!((sampled.four_wavelen() - expected).abs() < 2e-3);
+            assert!((sampled.center_frequency() - morlet.center_frequency()).abs() < 1e-3);
+            assert!((sampled.frequency_spread() / morlet.frequency_spread() - 1.0).abs() < 1e-3);
+            assert!((sampled.time_spread() / morlet.time_spread() - 1.0).abs() < 1e-3);
+            assert!((sampled.efolding_time() / morlet.efolding_time() - 1.0).abs() < 1e-3);
+        }
+    }
+
+    #[test]
+    fn test_wavelet_metadata_cache() {
+        let morse = MorseWavelet::<f64>::new(3.0, 20.0);
+        let cache = morse.metadata_cache().unwrap();
+        assert!(cache.efolding_time.get().is_none());
+
+        let efolding = morse.efolding_time();
+        assert_eq!(cache.efolding_time.get(), Some(&efolding));
+        assert_eq!(morse.efolding_time(), efolding);
+
+        // The correction changes the response, so the cache starts over
+        let morlet = MorletWavelet::<f64>::new(0.5);
+        let admissibility = morlet.admissibility_constant();
+        let morlet = morlet.with_correction(true);
+        assert!(morlet.metadata.admissibility_constant.get().is_none());
+        assert!(morlet.admissibility_constant() < admissibility);
+    }
+
+    #[test]
+    fn test_wavelet_heisenberg_box() {
+        let bound = 1.0 / (4.0 * std::f64::consts::PI);
+
+        // The Gaussian Morlet wavelet attains the uncertainty bound
+        let (time, frequency) = MorletWavelet::<f64>::new(1.5).heisenberg_box(8.0);
+        assert!((time * frequency / bound - 1.0).abs() < 1e-12);
+
+        let wavelets: [Box<dyn Wavelet<f64>>; 4] = [
+            Box::new(PaulWavelet::new(4)),
+            Box::new(DogWavelet::new(2)),
+            Box::new(MorseWavelet::new(3.0, 20.0)),
+            Box::new(BumpWavelet::default()),
+        ];
+        for wavelet in wavelets {
+            let (time, frequency) = wavelet.heisenberg_box(8.0);
+            assert!(time * frequency > bound * 0.999);
+        }
+    }
+
+    #[test]
+    fn test_paul_wavelet_metadata() {
+        for m in 1..6 {
+            let paul = PaulWavelet::<f64>::new(m);
+
+            // int (norm (m u)^m e^(-m u))^2 / u du = 2 pi / m
+            let expected = 2.0 * std::f64::consts::PI / f64::from(m);
+            assert!((paul.admissibility_constant() / expected - 1.0).abs() < 1e-3);
+
+            // The closed form e-folding time matches the numeric default
+            let custom = CustomWavelet::new(
+                |omega: f64| {
+                    let u = omega / f64::from(m);
+                    Complex::new(paul.frequency_response(u.max(0.0)).unwrap(), 0.0)
+                },
+                f64::from(m),
+            );
+            assert!((custom.efolding_time() / paul.efolding_time() - 1.0).abs() < 1e-3);
+        }
+    }
+}
@@ -0,0 +1,250 @@
+use std::ops::Range;
+
+use crate::{cast, Complex, Float};
+
+use super::{mother_len, time_response, time_support, MetadataCache, Wavelet};
+
+/// Bump wavelet, as in MATLAB's `cwt`.
+///
+/// The frequency response `e^(1 - 1 / (1 - (w - mu)^2 / sigma^2))` is only non-zero for
+/// `|w - mu| < sigma`, which gives sharp frequency localization. The transform skips
+/// all bins outside that band.
+pub struct BumpWavelet<T: Float = f32> {
+    mu: T,
+    sigma: T,
+    four_wavelen: T,
+    imag_frequency: bool,
+    double_sided: bool,
+    mother: Vec<T>,
+
+    support: T,
+    metadata: MetadataCache,
+}
+
+impl<T: Float> BumpWavelet<T> {
+    /// Bump wavelet with peak frequency `mu` and half width `sigma`, in radians per unit time
+    pub fn new(mu: T, sigma: T) -> Self {
+        assert!(sigma > T::zero(), "bump wavelet sigma must be positive");
+        assert!(sigma < mu, "bump wavelet sigma must be smaller than mu");
+
+        let (m, s) = (mu.to_f64().unwrap(), sigma.to_f64().unwrap());
+
+        // The Fourier wavelength is where u * response(u)^2 peaks, the root of
+        // 1/u = 4 v mu / (sigma (1 - v^2)^2) with u = 1 + v sigma / mu
+        let (mut lower, mut upper) = (0.0, 1.0);
+        for _ in 0..64 {
+            let v: f64 = (lower + upper) / 2.0;
+            let u = 1.0 + v * s / m;
+            if 1.0 / u > 4.0 * v * m / (s * (1.0 - v * v).powi(2)) {
+                lower = v;
+            } else {
+                upper = v;
+            }
+        }
+
+        let mut wavelet = Self {
+            mu,
+            sigma,
+            four_wavelen: cast(1.0 / (1.0 + lower * s / m)),
+            imag_frequency: false,
+            double_sided: false,
+            mother: vec![],
+            metadata: MetadataCache::default(),
+            support: T::zero(),
+        };
+
+        // The response is smooth, so the time response decays quickly but has no closed form
+        let min = 3.0 * wavelet.bandwidth().to_f64().unwrap();
+        wavelet.support = cast(time_support(|t| wavelet.time_response(t).norm(), min));
+
+        wavelet
+    }
+
+    /// Peak frequency `mu`, in radians per unit time of the mother wavelet
+    #[inline]
+    pub fn mu(&self) -> T {
+        self.mu
+    }
+
+    /// Half width `sigma` of the frequency support, in radians per unit time
+    #[inline]
+    pub fn sigma(&self) -> T {
+        self.sigma
+    }
+
+    /// Half width of the frequency support relative to the peak frequency
+    #[inline(always)]
+    fn half_width(&self) -> f64 {
+        (self.sigma / self.mu).to_f64().unwrap()
+    }
+
+    /// Frequency response at `u`, relative to the peak frequency
+    #[inline(always)]
+    fn response(&self, u: f64) -> f64 {
+        let v = (u - 1.0) / self.half_width();
+        if v.abs() < 1.0 {
+            (1.0 - 1.0 / (1.0 - v * v)).exp()
+        } else {
+            0.0
+        }
+    }
+
+    /// Time response at `t` peak periods
+    fn time_response(&self, t: f64) -> Complex<f64> {
+        let width = self.half_width();
+        time_response(|u| self.response(u), 1.0 - width, 1.0 + width, t)
+    }
+}
+
+impl<T: Float> Default for BumpWavelet<T> {
+    /// MATLAB's default bump wavelet with `mu = 5` and `sigma = 0.6`
+    fn default() -> Self {
+        Self::new(cast(5.0), cast(0.6))
+    }
+}
+
+impl<T: Float> Wavelet<T> for BumpWavelet<T> {
+    fn generate_mother(&mut self, size: usize) -> Vec<T> {
+        let len = mother_len(size, 1.0 + self.half_width());
+        let step = 2.0 / size as f64;
+
+        let mother: Vec<T> = (0..len)
+            .map(|i| cast(self.response(i as f64 * step)))
+            .collect();
+
+        self.mother = mother.clone();
+
+        mother
+    }
+
+    fn generate(&self, size: usize, scale: T) -> Vec<Complex<T>> {
+        let width = self.get_support(scale);
+        let scale = scale.to_f64().unwrap();
+        let norm = size as f64 / scale;
+
+        (0..width * 2 + 1)
+            .map(|i| {
+                let value = self.time_response((i - width) as f64 / scale) * norm;
+                Complex::new(cast(value.re), cast(value.im))
+            })
+            .collect()
+    }
+
+    /// Time spread `mu / (2 pi sigma)` in units of the peak period, the inverse of the
+    /// relative frequency half width
+    #[inline(always)]
+    fn bandwidth(&self) -> T {
+        self.mu / (T::TAU() * self.sigma)
+    }
+
+    #[inline(always)]
+    fn four_wavelen(&self) -> T {
+        self.four_wavelen
+    }
+
+    #[inline(always)]
+    fn imag_frequency(&self) -> bool {
+        self.imag_frequency
+    }
+
+    #[inline(always)]
+    fn is_double_sided(&self) -> bool {
+        self.double_sided
+    }
+
+    #[inline(always)]
+    fn mother(&self) -> &[T] {
+        self.mother.as_slice()
+    }
+
+    fn get_support(&self, scale: T) -> isize {
+        (self.support * scale).to_isize().unwrap()
+    }
+
+    fn frequency_support(&self) -> Option<Range<T>> {
+        let width = self.sigma / self.mu;
+        Some(T::one() - width..T::one() + width)
+    }
+
+    #[inline(always)]
+    fn frequency_response(&self, u: T) -> Option<T> {
+        Some(cast(self.response(u.to_f64().unwrap())))
+    }
+
+    fn metadata_cache(&self) -> Option<&MetadataCache> {
+        Some(&self.metadata)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bump_wavelet_generate_mother() {
+        let size = 1024;
+        let mut bump = BumpWavelet::<f64>::default();
+        let mother = bump.generate_mother(size);
+
+        assert_eq!(mother.len(), size);
+        assert_eq!(mother[size / 2], 1.0);
+
+        // Non-zero only within |w - mu| < sigma, or |u - 1| < 0.12
+        let range = bump.frequency_support().unwrap();
+        for (j, &value) in mother.iter().enumerate() {
+            let u = 2.0 * j as f64 / size as f64;
+            assert_eq!(value > 0.0, range.contains(&u) && u != range.start);
+            if value > 0.0 {
+                let v = (5.0 * u - 5.0) / 0.6;
+                assert!((value - (1.0 - 1.0 / (1.0 - v * v)).exp()).abs() < 1e-12);
+            }
+        }
+    }
+
+    #[test]
+    fn test_bump_wavelet_four_wavelen() {
+        let bump = BumpWavelet::<f64>::default();
+
+        // Maximum of u * response(u)^2 found by brute force
+        let step = 1e-6;
+        let peak = (0..240_000)
+            .map(|i| 0.88 + i as f64 * step)
+            .max_by(|&a, &b| {
+                (a * bump.response(a).powi(2)).total_cmp(&(b * bump.response(b).powi(2)))
+            })
+            .unwrap();
+
+        assert!((bump.four_wavelen() - 1.0 / peak).abs() < 1e-5);
+        assert!(bump.four_wavelen() < 1.0);
+    }
+
+    #[test]
+    fn test_bump_wavelet_generate_time() {
+        let bump = BumpWavelet::<f64>::default();
+        let size = 64;
+        let scale = 4.0;
+        let result = bump.generate(size, scale);
+        let width = bump.get_support(scale) as usize;
+
+        assert_eq!(result.len(), 2 * width + 1);
+
+        // Oscillates at the peak period, with a symmetric envelope
+        let center = result[width];
+        assert!(center.im.abs() < 1e-9 * center.re);
+        assert!((result[width + 4] - center).norm() < 0.2 * center.norm());
+        assert!((result[width + 2] + center).norm() < 0.2 * center.norm());
+        assert!((result[width - 3] - result[width + 3].conj()).norm() < 1e-9);
+
+        // Negligible beyond the support
+        for t in [0.1, 0.5, 1.0] {
+            let outside = bump.time_response(bump.support + t) * (size as f64 / scale);
+            assert!(outside.norm() < 1e-3 * center.norm());
+        }
+    }
+
+    #[test]
+    fn test_bump_wavelet_cwt_peak() {
+        crate::wavelet::tests::assert_cwt_peak(BumpWavelet::default(), 3.0);
+        crate::wavelet::tests::assert_cwt_peak(BumpWavelet::new(6.0, 1.5), 3.0);
+    }
+}
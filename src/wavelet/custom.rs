@@ -0,0 +1,406 @@
+use std::cmp::Ordering;
+use std::sync::{Arc, Mutex};
+
+use crate::fft::{default_planner, FftBackend, FftPlanner, RustFftBackend};
+use crate::padding::padded_len;
+use crate::{cast, Complex, Float};
+
+use super::{frequency_cutoff, mother_len, MetadataCache, Wavelet};
+
+/// FFT backend and the size it was planned for
+type PlannedFft<T> = (usize, Box<dyn FftBackend<T> + Send>);
+
+/// Wavelet defined by a frequency response closure, for one-off experimental wavelets.
+///
+/// The closure maps an angular frequency `omega` to the response of the mother wavelet,
+/// with its peak at the given angular frequency. The response must be purely real or purely
+/// imaginary on positive frequencies. If it is non-zero on negative frequencies the wavelet
+/// is treated as real valued, so the negative side must be the conjugate of the positive side.
+///
+/// The sampled mother, the time-domain kernel, whether the response is real or imaginary and
+/// one or two sided, and the remaining metadata are derived from the closure.
+pub struct CustomWavelet<F, T: Float = f32>
+where
+    F: Fn(T) -> Complex<T>,
+{
+    response: F,
+    peak_omega: T,
+    four_wavelen: T,
+    imag_frequency: bool,
+    double_sided: bool,
+    mother: Vec<T>,
+
+    bandwidth: T,
+    support: Option<T>,
+    cutoff: f64,
+
+    // Inverse FFT for the time-domain kernel, replanned when the kernel length changes
+    planner: Arc<dyn FftPlanner<T>>,
+    fft: Mutex<Option<PlannedFft<T>>>,
+    metadata: MetadataCache,
+}
+
+impl<F, T: Float> CustomWavelet<F, T>
+where
+    F: Fn(T) -> Complex<T>,
+{
+    /// Wavelet with frequency response `response(omega)`, peaking at `peak_omega`
+    pub fn new(response: F, peak_omega: T) -> Self {
+        assert!(peak_omega > T::zero(), "peak frequency must be positive");
+
+        let at = |u: f64| response(peak_omega * cast(u));
+        let magnitude = |u: f64| at(u).norm().to_f64().unwrap();
+        let peak = magnitude(1.0);
+        assert!(
+            peak > 0.0,
+            "frequency response must be non-zero at the peak frequency"
+        );
+
+        let cutoff = frequency_cutoff(magnitude);
+
+        // Probe both sides of the response to classify it
+        let probes = 1024;
+        let (mut real, mut imag, mut negative) = (0.0f64, 0.0f64, 0.0f64);
+        let mut hermitian = true;
+        for k in 1..=probes {
+            let u = cutoff * k as f64 / probes as f64;
+            let (positive, mirrored) = (at(u), at(-u));
+
+            real = real.max(positive.re.abs().to_f64().unwrap());
+            imag = imag.max(positive.im.abs().to_f64().unwrap());
+            negative = negative.max(mirrored.norm().to_f64().unwrap());
+            hermitian &= (mirrored - positive.conj()).norm().to_f64().unwrap() <= 1e-6 * peak;
+        }
+
+        let tolerance = 1e-6 * peak;
+        assert!(
+            real <= tolerance || imag <= tolerance,
+            "frequency response must be purely real or purely imaginary"
+        );
+        let double_sided = negative > tolerance;
+        assert!(
+            !double_sided || hermitian,
+            "two sided frequency response must be conjugate symmetric"
+        );
+
+        // Fourier wavelength where u * |response(u)|^2 peaks, and spread of |response(u)|
+        let steps = 16384;
+        let du = cutoff / steps as f64;
+        let (mut fourier, mut fourier_power) = (1.0, 0.0);
+        let (mut weight, mut mean, mut square) = (0.0, 0.0, 0.0);
+        for k in 1..=steps {
+            let u = k as f64 * du;
+            let value = magnitude(u);
+
+            if u * value * value > fourier_power {
+                fourier_power = u * value * value;
+                fourier = u;
+            }
+
+            weight += value;
+            mean += value * u;
+            square += value * u * u;
+        }
+        let (mean, square) = (mean / weight, square / weight);
+        let spread = (square - mean * mean).max(f64::EPSILON).sqrt();
+
+        Self {
+            four_wavelen: cast(1.0 / fourier),
+            imag_frequency: imag > tolerance,
+            double_sided,
+            mother: vec![],
+            metadata: MetadataCache::default(),
+            bandwidth: cast(1.0 / (std::f64::consts::TAU * spread)),
+            support: None,
+            cutoff,
+            planner: default_planner(),
+            fft: Mutex::new(None),
+            response,
+            peak_omega,
+        }
+    }
+
+    /// Set the time-domain support, in units of the peak period, instead of deriving it
+    /// from the spread of the frequency response
+    pub fn with_support(mut self, support: T) -> Self {
+        self.support = Some(support);
+        self
+    }
+
+    /// Angular frequency the response peaks at, mapped to `u = 1`
+    #[inline]
+    pub fn peak_omega(&self) -> T {
+        self.peak_omega
+    }
+
+    /// Response at `u` relative to the peak frequency
+    #[inline(always)]
+    fn response(&self, u: T) -> Complex<T> {
+        (self.response)(self.peak_omega * u)
+    }
+
+    /// Real or imaginary part of the response at `u`, whichever the mother holds
+    #[inline(always)]
+    fn mother_response(&self, u: T) -> T {
+        let value = self.response(u);
+        if self.imag_frequency {
+            value.im
+        } else {
+            value.re
+        }
+    }
+}
+
+impl<F, T: Float> Wavelet<T> for CustomWavelet<F, T>
+where
+    F: Fn(T) -> Complex<T>,
+{
+    fn generate_mother(&mut self, size: usize) -> Vec<T> {
+        let len = mother_len(size, self.cutoff);
+        let step: T = cast(2.0 / size as f64);
+
+        let mother: Vec<T> = (0..len)
+            .map(|i| self.mother_response(cast::<T>(i as f64) * step))
+            .collect();
+
+        self.mother = mother.clone();
+
+        mother
+    }
+
+    /// Sample the daughter frequency response on a grid wide enough to avoid wrapping
+    /// the kernel, and inverse FFT it
+    fn generate(&self, size: usize, scale: T) -> Vec<Complex<T>> {
+        let width = self.get_support(scale);
+        let len = padded_len(4 * width as usize + 2);
+        let step = scale / cast(len as f64);
+
+        // Bin k holds frequency k, and bin len - k frequency -k. The Nyquist bin holds both.
+        let sample = || -> Vec<Complex<T>> {
+            (0..len)
+                .map(|k| match k.cmp(&(len / 2)) {
+                    Ordering::Less => self.response(step * cast(k as f64)),
+                    Ordering::Equal => {
+                        let k: T = cast(k as f64);
+                        (self.response(step * k) + self.response(-step * k)) / cast::<T>(2.0)
+                    }
+                    Ordering::Greater => self.response(step * cast(k as f64 - len as f64)),
+                })
+                .collect()
+        };
+
+        let mut spectrum = sample();
+        let transformed = {
+            let mut fft = self.fft.lock().unwrap();
+            if !matches!(*fft, Some((planned, _)) if planned == len) {
+                *fft = self.planner.plan(len).ok().map(|backend| (len, backend));
+            }
+            fft.as_mut()
+                .is_some_and(|(_, backend)| backend.inverse_in_place(&mut spectrum).is_ok())
+        };
+
+        // Kernels can't report errors, so the pure Rust backend stands in if the planner fails
+        if !transformed {
+            spectrum = sample();
+            RustFftBackend::new(len)
+                .inverse_in_place(&mut spectrum)
+                .expect("the RustFFT backend never fails");
+        }
+
+        let norm: T = cast(size as f64 / len as f64);
+        (-width..=width)
+            .map(|t| spectrum[t.rem_euclid(len as isize) as usize] * norm)
+            .collect()
+    }
+
+    #[inline(always)]
+    fn bandwidth(&self) -> T {
+        self.bandwidth
+    }
+
+    #[inline(always)]
+    fn four_wavelen(&self) -> T {
+        self.four_wavelen
+    }
+
+    #[inline(always)]
+    fn imag_frequency(&self) -> bool {
+        self.imag_frequency
+    }
+
+    #[inline(always)]
+    fn is_double_sided(&self) -> bool {
+        self.double_sided
+    }
+
+    #[inline(always)]
+    fn mother(&self) -> &[T] {
+        self.mother.as_slice()
+    }
+
+    fn get_support(&self, scale: T) -> isize {
+        match self.support {
+            Some(support) => (support * scale).to_isize().unwrap(),
+            None => (self.bandwidth * scale * cast(3.0)).to_isize().unwrap(),
+        }
+    }
+
+    #[inline(always)]
+    fn frequency_response(&self, u: T) -> Option<T> {
+        Some(self.mother_response(u))
+    }
+
+    fn set_planner(&mut self, planner: Arc<dyn FftPlanner<T>>) {
+        self.planner = planner;
+        *self.fft.get_mut().unwrap() = None;
+    }
+
+    fn metadata_cache(&self) -> Option<&MetadataCache> {
+        Some(&self.metadata)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DogWavelet, FastCwt, LogFreqs, MorletWavelet};
+
+    fn morlet(omega: f64) -> Complex<f64> {
+        let norm = (2.0 * std::f64::consts::PI).sqrt() * crate::IPI4;
+        Complex::new(
+            norm * (-(omega - 2.0 * std::f64::consts::PI).powi(2) / 2.0).exp(),
+            0.0,
+        )
+    }
+
+    /// Compare two kernels of possibly different support, aligned on their centers
+    fn assert_kernels_match(a: &[Complex<f64>], b: &[Complex<f64>]) {
+        let offset = a.len().abs_diff(b.len()) / 2;
+        let (long, short) = if a.len() > b.len() { (a, b) } else { (b, a) };
+        for (x, y) in long[offset..].iter().zip(short) {
+            assert!((x - y).norm() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn test_custom_wavelet_morlet() {
+        let mut custom = CustomWavelet::new(
+            |omega: f64| {
+                if omega >= 0.0 {
+                    morlet(omega)
+                } else {
+                    Complex::new(0.0, 0.0)
+                }
+            },
+            2.0 * std::f64::consts::PI,
+        );
+        let mut morlet = MorletWavelet::<f64>::new(1.0);
+
+        assert_eq!(custom.peak_omega(), 2.0 * std::f64::consts::PI);
+        assert!((custom.center_frequency() - 1.0).abs() < 1e-3);
+        assert!(!custom.imag_frequency());
+        assert!(!custom.is_double_sided());
+        assert!((custom.four_wavelen() - 0.9876).abs() < 1e-3);
+        assert!((custom.bandwidth() - 1.0).abs() < 1e-3);
+        assert_eq!(custom.get_support(10.0), morlet.get_support(10.0));
+
+        let a = custom.generate_mother(1024);
+        let b = morlet.generate_mother(1024);
+        assert_eq!(a.len(), 1024);
+        for (x, y) in a.iter().zip(b.iter()) {
+            assert!((x - y).abs() < 1e-9);
+        }
+
+        assert_kernels_match(&custom.generate(64, 4.0), &morlet.generate(64, 4.0));
+    }
+
+    #[test]
+    fn test_custom_wavelet_dog() {
+        let input = crate::util::chirp(1000.0, 1000, 5.0, 100.0);
+
+        // Even and odd derivative of Gaussian responses, peaking at sqrt(m)
+        for m in [1, 2] {
+            let gamma: f64 = if m == 1 {
+                0.886_226_925_452_758
+            } else {
+                1.329_340_388_179_137
+            };
+            let norm = (2.0 * std::f64::consts::PI).sqrt() / gamma.sqrt();
+            let custom = CustomWavelet::new(
+                move |omega: f64| {
+                    Complex::new(0.0, omega).powi(m)
+                        * (-1f64).powi(m + 1)
+                        * norm
+                        * (-omega * omega / 2.0).exp()
+                },
+                (m as f64).sqrt(),
+            );
+            let dog = DogWavelet::<f64>::new(m as u32);
+
+            assert_eq!(custom.imag_frequency(), dog.imag_frequency());
+            assert!(custom.is_double_sided());
+            assert!((custom.four_wavelen() - dog.four_wavelen()).abs() < 1e-3);
+
+            assert_kernels_match(&custom.generate(64, 16.0), &dog.generate(64, 16.0));
+
+            let scales = LogFreqs::new(1000, 5.0, 200.0, 32);
+            let a = FastCwt::new(custom, scales.clone(), true).cwt(&input);
+            let b = FastCwt::new(dog, scales, true).cwt(&input);
+            for (x, y) in a.as_slice().iter().zip(b.as_slice()) {
+                assert!((x - y).norm() < 1e-9);
+            }
+        }
+    }
+
+    #[test]
+    fn test_custom_wavelet_planner() {
+        use crate::fft::RustFftBackend;
+        use crate::ConvolutionMode;
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let custom = CustomWavelet::new(
+            |omega: f64| {
+                if omega >= 0.0 {
+                    morlet(omega)
+                } else {
+                    Complex::new(0.0, 0.0)
+                }
+            },
+            2.0 * std::f64::consts::PI,
+        );
+
+        // Kernels are planned through the transform's planner, once per kernel length
+        let planned = Arc::new(AtomicUsize::new(0));
+        let counter = planned.clone();
+        let mut fast_cwt = FastCwt::new(custom, LogFreqs::new(1000, 50.0, 60.0, 2), true)
+            .with_convolution(ConvolutionMode::Direct)
+            .with_planner(move |size| -> crate::fft::PlanResult<f64> {
+                counter.fetch_add(1, Ordering::Relaxed);
+                Ok(Box::new(RustFftBackend::new(size)))
+            });
+
+        let input = crate::util::chirp(1000.0, 256, 5.0, 100.0);
+        fast_cwt.cwt(&input);
+        let count = planned.load(Ordering::Relaxed);
+        assert!(count > 0);
+
+        // Both scales and 55 Hz have 256 sample kernels, so the cached plan is reused
+        fast_cwt.wavelet().generate(1, 1000.0 / 55.0);
+        fast_cwt.wavelet().generate(1, 1000.0 / 55.0);
+        assert_eq!(planned.load(Ordering::Relaxed), count);
+
+        // A new kernel length is planned exactly once
+        fast_cwt.wavelet().generate(1, 80.0);
+        fast_cwt.wavelet().generate(1, 80.0);
+        assert_eq!(planned.load(Ordering::Relaxed), count + 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "purely real or purely imaginary")]
+    fn test_custom_wavelet_complex_response() {
+        CustomWavelet::new(
+            |omega: f32| Complex::new(1.0, 1.0) * (-(omega - 1.0).powi(2)).exp(),
+            1.0,
+        );
+    }
+}
@@ -0,0 +1,361 @@
+use crate::{cast, Complex, Float};
+
+use super::{frequency_cutoff, mother_len, MetadataCache, Wavelet};
+
+/// Derivative of Gaussian wavelet of order `m` (Torrence & Compo, 1998).
+///
+/// The wavelet is real valued, so its frequency response covers negative frequencies
+/// as well. Even orders have a real, symmetric response and odd orders an imaginary,
+/// antisymmetric one. Order 2 is the Mexican hat or Ricker wavelet.
+pub struct DogWavelet<T: Float = f32> {
+    order: u32,
+    four_wavelen: T,
+    imag_frequency: bool,
+    double_sided: bool,
+    mother: Vec<T>,
+
+    norm: T,
+    cutoff: f64,
+    support: T,
+    metadata: MetadataCache,
+}
+
+/// Probabilists' Hermite polynomial `He_m(x)`, so that the m-th derivative of
+/// `e^(-x^2/2)` is `(-1)^m He_m(x) e^(-x^2/2)`
+fn hermite(m: u32, x: f64) -> f64 {
+    let (mut prev, mut value) = (1.0, x);
+    if m == 0 {
+        return prev;
+    }
+
+    for n in 1..m {
+        (prev, value) = (value, x * value - f64::from(n) * prev);
+    }
+
+    value
+}
+
+/// Gamma(m + 1/2) for an integer `m`
+fn gamma_half(m: u32) -> f64 {
+    (1..=m).fold(std::f64::consts::PI.sqrt(), |acc, n| {
+        acc * (f64::from(n) - 0.5)
+    })
+}
+
+impl<T: Float> DogWavelet<T> {
+    pub fn new(order: u32) -> Self {
+        assert!(order > 0, "DOG wavelet order must be at least 1");
+
+        // Frequency response (-1)^(m+1) (i s w)^m e^(-(s w)^2/2), scaled by sqrt(2pi) like the
+        // Morlet mother. The phase is folded into the sign of the mother, which is real or imaginary.
+        let phase = Complex::<f64>::i().powi(order as i32) * (-1f64).powi(order as i32 + 1);
+        let imag_frequency = order % 2 == 1;
+        let phase = if imag_frequency { phase.im } else { phase.re };
+        let norm = phase * std::f64::consts::TAU.sqrt() / gamma_half(order).sqrt();
+
+        // The polynomial factor widens the Gaussian envelope, so find where the wavelet
+        // drops below 1e-3 of its peak
+        let envelope = |eta: f64| (hermite(order, eta) * (-eta * eta / 2.0).exp()).abs();
+        let step = 0.01;
+        let mut eta = 0.0;
+        let mut peak: f64 = 0.0;
+        while eta < f64::from(order).sqrt() || envelope(eta) >= 1e-3 * peak {
+            peak = peak.max(envelope(eta));
+            eta += step;
+        }
+
+        Self {
+            order,
+            four_wavelen: cast((f64::from(order) / (f64::from(order) + 0.5)).sqrt()),
+            imag_frequency,
+            double_sided: true,
+            mother: vec![],
+            metadata: MetadataCache::default(),
+            norm: cast(norm),
+            cutoff: frequency_cutoff(|u| {
+                let sw = f64::from(order).sqrt() * u;
+                sw.powi(order as i32) * (-sw * sw / 2.0).exp()
+            }),
+            support: cast(eta * f64::from(order).sqrt() / std::f64::consts::TAU),
+        }
+    }
+
+    /// Mexican hat (Ricker) wavelet, the second derivative of a Gaussian
+    pub fn mexican_hat() -> Self {
+        Self::new(2)
+    }
+
+    /// Order `m` of the wavelet
+    #[inline]
+    pub fn order(&self) -> u32 {
+        self.order
+    }
+
+    /// Convert a time in units of scale to the dimensionless Torrence & Compo time `eta`
+    #[inline(always)]
+    fn eta(&self, t: T) -> T {
+        t * T::TAU() / cast::<T>(f64::from(self.order)).sqrt()
+    }
+
+    /// Frequency response magnitude at `u`, relative to the peak frequency
+    #[inline(always)]
+    fn response(&self, u: T) -> T {
+        // Peak of the response at s*w = sqrt(m)
+        let sw = cast::<T>(f64::from(self.order)).sqrt() * u;
+        self.norm * sw.powi(self.order as i32) * (-(sw * sw) / cast(2.0)).exp()
+    }
+}
+
+impl<T: Float> Wavelet<T> for DogWavelet<T> {
+    fn generate_mother(&mut self, size: usize) -> Vec<T> {
+        let len = mother_len(size, self.cutoff);
+        let step: T = cast(2.0 / size as f64);
+
+        let mother: Vec<T> = (0..len)
+            .map(|i| self.response(cast::<T>(i as f64) * step))
+            .collect();
+
+        self.mother = mother.clone();
+
+        mother
+    }
+
+    fn generate(&self, size: usize, scale: T) -> Vec<Complex<T>> {
+        let width = self.get_support(scale);
+
+        // -He_m(eta) e^(-eta^2/2) / sqrt(Gamma(m + 1/2)), stretched by the ratio of the peak period to s
+        let m = f64::from(self.order);
+        let norm = -cast::<T>(size as f64 * std::f64::consts::TAU / m.sqrt())
+            / cast::<T>(gamma_half(self.order).sqrt());
+
+        let mut output: Vec<Complex<T>> = Vec::with_capacity((width * 2 + 1) as usize);
+
+        for i in 0..width * 2 + 1 {
+            let eta = self.eta(cast::<T>((i - width) as f64) / scale);
+            let polynomial: T = cast(hermite(self.order, eta.to_f64().unwrap()));
+            let value = norm * polynomial * (-(eta * eta) / cast(2.0)).exp() / scale;
+
+            output.push(Complex::new(value, T::zero()));
+        }
+
+        output
+    }
+
+    /// Standard deviation of the Gaussian envelope, in units of the peak period
+    #[inline(always)]
+    fn bandwidth(&self) -> T {
+        cast::<T>(f64::from(self.order)).sqrt() / T::TAU()
+    }
+
+    #[inline(always)]
+    fn four_wavelen(&self) -> T {
+        self.four_wavelen
+    }
+
+    #[inline(always)]
+    fn imag_frequency(&self) -> bool {
+        self.imag_frequency
+    }
+
+    #[inline(always)]
+    fn is_double_sided(&self) -> bool {
+        self.double_sided
+    }
+
+    #[inline(always)]
+    fn mother(&self) -> &[T] {
+        self.mother.as_slice()
+    }
+
+    fn get_support(&self, scale: T) -> isize {
+        (self.support * scale).to_isize().unwrap()
+    }
+
+    #[inline(always)]
+    fn frequency_response(&self, u: T) -> Option<T> {
+        Some(self.response(u))
+    }
+
+    fn metadata_cache(&self) -> Option<&MetadataCache> {
+        Some(&self.metadata)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{FastCwt, LogScales};
+    use std::f64::consts::PI;
+
+    // scipy.signal.ricker, with an odd number of points so samples fall on integer offsets
+    fn ricker(points: usize, a: f64) -> Vec<f64> {
+        let amplitude = 2.0 / ((3.0 * a).sqrt() * PI.powf(0.25));
+        (0..points)
+            .map(|i| {
+                let x = i as f64 - (points as f64 - 1.0) / 2.0;
+                let t = x * x / (a * a);
+                amplitude * (1.0 - t) * (-t / 2.0).exp()
+            })
+            .collect()
+    }
+
+    // Torrence & Compo (1998), table 1
+    fn reference_time(m: u32, eta: f64) -> f64 {
+        let derivative = (-1f64).powi(m as i32) * hermite(m, eta) * (-eta * eta / 2.0).exp();
+        (-1f64).powi(m as i32 + 1) / gamma_half(m).sqrt() * derivative
+    }
+
+    #[test]
+    fn test_hermite() {
+        assert_eq!(hermite(0, 2.0), 1.0);
+        assert_eq!(hermite(1, 2.0), 2.0);
+        assert_eq!(hermite(2, 2.0), 3.0);
+        assert_eq!(hermite(3, 2.0), 2.0);
+        assert_eq!(hermite(4, 2.0), -5.0);
+        assert!((gamma_half(2) - 1.329_340_388_179_137).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_dog_wavelet_flags() {
+        let even = DogWavelet::<f32>::new(2);
+        assert!(even.is_double_sided());
+        assert!(!even.imag_frequency());
+
+        let odd = DogWavelet::<f32>::new(3);
+        assert!(odd.is_double_sided());
+        assert!(odd.imag_frequency());
+    }
+
+    #[test]
+    fn test_dog_wavelet_generate_mother() {
+        let size = 1024;
+
+        // (-1)^(m+1) (i s w)^m e^(-(s w)^2/2), real for even and imaginary for odd orders
+        for (m, phase) in [(1, 1.0), (2, 1.0), (3, -1.0), (4, -1.0)] {
+            let mut dog = DogWavelet::<f64>::new(m);
+            let mother = dog.generate_mother(size);
+            assert_eq!(mother.len(), mother_len(size, dog.cutoff));
+
+            for (j, &value) in mother.iter().enumerate() {
+                let sw = (m as f64).sqrt() * 2.0 * j as f64 / size as f64;
+                let expected = phase * (2.0 * PI).sqrt() / gamma_half(m).sqrt()
+                    * sw.powi(m as i32)
+                    * (-sw * sw / 2.0).exp();
+                assert!((value - expected).abs() < 1e-12);
+            }
+
+            let peak = mother
+                .iter()
+                .enumerate()
+                .max_by(|a, b| a.1.abs().total_cmp(&b.1.abs()))
+                .unwrap()
+                .0;
+            assert_eq!(peak, size / 2);
+        }
+    }
+
+    #[test]
+    fn test_dog_wavelet_generate_time() {
+        let size = 64;
+        let scale = 8.0;
+
+        for m in 1..5 {
+            let dog = DogWavelet::<f64>::new(m);
+            let result = dog.generate(size, scale);
+            let width = dog.get_support(scale);
+
+            assert_eq!(result.len(), (width * 2 + 1) as usize);
+
+            // Reference shape stretched by 2*pi/sqrt(m), with the amplitude of the time kernel
+            for (i, value) in result.iter().enumerate() {
+                let eta = (i as isize - width) as f64 / scale * 2.0 * PI / (m as f64).sqrt();
+                let expected =
+                    reference_time(m, eta) * 2.0 * PI / (m as f64).sqrt() * size as f64 / scale;
+
+                assert!((value.re - expected).abs() < 1e-9);
+                assert_eq!(value.im, 0.0);
+            }
+
+            // Time kernel is the inverse Fourier transform of the two sided frequency response
+            let mut wavelet = DogWavelet::<f64>::new(m);
+            wavelet.generate_mother(2);
+            let phase = if wavelet.imag_frequency() {
+                Complex::new(0.0, wavelet.mother[1])
+            } else {
+                Complex::new(wavelet.mother[1], 0.0)
+            } / ((m as f64).sqrt().powi(m as i32) * (-(m as f64) / 2.0).exp());
+
+            let du = 1.0 / 1024.0;
+            for (i, value) in result.iter().enumerate() {
+                let t = (i as isize - width) as f64 / scale;
+                let expected: Complex<f64> = (-8 * 1024..8 * 1024)
+                    .map(|j| {
+                        let sw = (m as f64).sqrt() * j as f64 * du;
+                        let response = phase * sw.powi(m as i32) * (-sw * sw / 2.0).exp();
+                        response * Complex::from_polar(du, 2.0 * PI * j as f64 * du * t)
+                    })
+                    .sum::<Complex<f64>>()
+                    * (size as f64 / scale);
+
+                assert!((value - expected).norm() < 1e-6);
+            }
+        }
+    }
+
+    #[test]
+    fn test_dog_wavelet_four_wavelen() {
+        // Fourier wavelength 2*pi*s/sqrt(m + 1/2) relative to the peak period 2*pi*s/sqrt(m)
+        let dog = DogWavelet::<f32>::mexican_hat();
+        assert!((dog.four_wavelen() - 0.894_427_2).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_dog_wavelet_get_support() {
+        let dog = DogWavelet::<f32>::mexican_hat();
+
+        // (1 - eta^2) e^(-eta^2/2) drops below 1e-3 at eta = 4.44, or 0.99 peak periods
+        assert_eq!(dog.get_support(100.0), 99);
+    }
+
+    #[test]
+    fn test_dog_wavelet_matches_ricker() {
+        let n = 512;
+        let input: Vec<f64> = (0..n)
+            .map(|i| {
+                let t = i as f64;
+                (t / 9.0).sin() + 0.5 * (t / 23.0).cos() + if i == 200 { 4.0 } else { 0.0 }
+            })
+            .collect();
+
+        // Ricker width a is the Torrence & Compo scale, which peaks at sqrt(2)/(2*pi*a)
+        let widths = [2.0, 4.0, 8.0];
+        let fs = 1000.0 / (PI * 2f64.sqrt());
+        let scales = LogScales::new(1000, fs / widths[2], fs / widths[0], 3);
+        let mut fast_cwt = FastCwt::new(DogWavelet::mexican_hat(), scales, true);
+        let result = fast_cwt.cwt(&input);
+
+        for (row, &a) in result.rows().zip(widths.iter()) {
+            // scipy.signal.cwt: np.convolve(data, ricker(min(10 * a, n), a), mode='same')
+            let kernel = ricker(10 * a as usize + 1, a);
+            let half = (kernel.len() - 1) / 2;
+            let expected: Vec<f64> = (0..n)
+                .map(|i| {
+                    kernel
+                        .iter()
+                        .enumerate()
+                        .filter_map(|(k, w)| {
+                            let j = (i + half).checked_sub(k)?;
+                            input.get(j).map(|x| x * w)
+                        })
+                        .sum()
+                })
+                .collect();
+
+            let peak = expected.iter().fold(0.0f64, |acc, x| acc.max(x.abs()));
+            for i in 64..n - 64 {
+                assert!(row[i].im.abs() < 1e-9);
+                assert!((row[i].re * a.sqrt() - expected[i]).abs() < 0.02 * peak);
+            }
+        }
+    }
+}
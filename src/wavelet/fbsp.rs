@@ -0,0 +1,291 @@
+use std::ops::Range;
+
+use crate::{cast, Complex, Float};
+
+use super::{mother_len, numeric_four_wavelen, MetadataCache, Wavelet};
+
+/// Frequency B-spline wavelet, matching PyWavelets' `fbspM-B-C`.
+///
+/// The wavelet is `sqrt(B) sinc(B t / M)^M e^(2 pi i C t)` with `sinc(x) = sin(pi x) / (pi x)`,
+/// so its frequency response is a B-spline of order `M` and width `B` centered on `C`. The
+/// center frequency `C` is mapped to `u = 1`, and the response is exactly zero outside
+/// `|u - 1| < B / (2C)`. Order 1 is the Shannon wavelet, see [`super::ShannonWavelet`].
+pub struct FbspWavelet<T: Float = f32> {
+    order: u32,
+    bandwidth: T,
+    center: T,
+    four_wavelen: T,
+    imag_frequency: bool,
+    double_sided: bool,
+    mother: Vec<T>,
+    metadata: MetadataCache,
+}
+
+/// Centered cardinal B-spline of order `m`, the `m`-fold convolution of the unit box on
+/// `-1/2..1/2`
+fn bspline(m: u32, x: f64) -> f64 {
+    let half = f64::from(m) / 2.0;
+    if x.abs() >= half {
+        return 0.0;
+    }
+
+    // Sum of truncated powers, sum_k (-1)^k C(m, k) (x + m/2 - k)_+^(m-1) / (m-1)!
+    let mut binomial = 1.0;
+    let mut sum = 0.0;
+    for k in 0..=m {
+        let y = x + half - f64::from(k);
+        if y > 0.0 {
+            let sign = if k % 2 == 0 { 1.0 } else { -1.0 };
+            sum += sign * binomial * y.powi(m as i32 - 1);
+        }
+        binomial *= f64::from(m - k) / f64::from(k + 1);
+    }
+
+    sum / (1..m).map(f64::from).product::<f64>()
+}
+
+impl<T: Float> FbspWavelet<T> {
+    /// Frequency B-spline wavelet of order `order`, bandwidth `fb` and center frequency `fc`,
+    /// as in PyWavelets' `fbsp{order}-{fb}-{fc}`
+    pub fn new(order: u32, fb: T, fc: T) -> Self {
+        assert!(order > 0, "frequency B-spline order must be at least 1");
+        assert!(fc > T::zero(), "center frequency must be positive");
+        assert!(fb > T::zero(), "bandwidth must be positive");
+        assert!(
+            fb < fc * cast(2.0),
+            "bandwidth must be below twice the center frequency"
+        );
+
+        let mut wavelet = Self {
+            order,
+            bandwidth: fb,
+            center: fc,
+            four_wavelen: T::one(),
+            imag_frequency: false,
+            double_sided: false,
+            mother: vec![],
+            metadata: MetadataCache::default(),
+        };
+        wavelet.four_wavelen = cast(numeric_four_wavelen(&wavelet));
+
+        wavelet
+    }
+
+    /// Order `M` of the B-spline
+    #[inline]
+    pub fn order(&self) -> u32 {
+        self.order
+    }
+
+    /// Bandwidth `B`, the width of the frequency support
+    #[inline]
+    pub fn fb(&self) -> T {
+        self.bandwidth
+    }
+
+    /// Center frequency `C`, mapped to `u = 1`
+    #[inline]
+    pub fn fc(&self) -> T {
+        self.center
+    }
+
+    /// Half width `B / 2C` of the frequency support relative to the center frequency
+    #[inline(always)]
+    fn half_width(&self) -> T {
+        self.bandwidth / (self.center * cast(2.0))
+    }
+
+    /// Wavelet at `t` in units of `1 / C`, PyWavelets' `psi(t / C)`
+    fn time_response(&self, t: T) -> Complex<T> {
+        let x = self.bandwidth * t / (self.center * cast(f64::from(self.order)));
+        let sinc = if x.is_zero() {
+            T::one()
+        } else {
+            (T::PI() * x).sin() / (T::PI() * x)
+        };
+
+        Complex::from_polar(
+            self.bandwidth.sqrt() * sinc.powi(self.order as i32),
+            T::TAU() * t,
+        )
+    }
+}
+
+impl<T: Float> Wavelet<T> for FbspWavelet<T> {
+    fn generate_mother(&mut self, size: usize) -> Vec<T> {
+        let len = mother_len(size, (T::one() + self.half_width()).to_f64().unwrap());
+        let step: T = cast(2.0 / size as f64);
+
+        let mother: Vec<T> = (0..len)
+            .map(|i| self.frequency_response(cast::<T>(i as f64) * step).unwrap())
+            .collect();
+
+        self.mother = mother.clone();
+
+        mother
+    }
+
+    /// The kernel is PyWavelets' wavelet in closed form, stretched so that the center
+    /// frequency falls at `1 / scale` cycles per sample
+    fn generate(&self, size: usize, scale: T) -> Vec<Complex<T>> {
+        let width = self.get_support(scale);
+        let norm = cast::<T>(size as f64) / scale;
+
+        (0..width * 2 + 1)
+            .map(|i| self.time_response(cast::<T>((i - width) as f64) / scale) * norm)
+            .collect()
+    }
+
+    /// Time spread `C / (pi B)` in units of the peak period, the inverse of the relative
+    /// frequency width
+    #[inline(always)]
+    fn bandwidth(&self) -> T {
+        self.center / (T::PI() * self.bandwidth)
+    }
+
+    #[inline(always)]
+    fn four_wavelen(&self) -> T {
+        self.four_wavelen
+    }
+
+    #[inline(always)]
+    fn imag_frequency(&self) -> bool {
+        self.imag_frequency
+    }
+
+    #[inline(always)]
+    fn is_double_sided(&self) -> bool {
+        self.double_sided
+    }
+
+    #[inline(always)]
+    fn mother(&self) -> &[T] {
+        self.mother.as_slice()
+    }
+
+    /// The envelope only decays as `|pi x|^-M`, so the support extends to where it drops
+    /// below 1e-3 of its peak rather than a multiple of the bandwidth
+    fn get_support(&self, scale: T) -> isize {
+        let m = f64::from(self.order);
+        let x = 1e3f64.powf(1.0 / m) / std::f64::consts::PI;
+        let t = cast::<T>(x * m) * self.center / self.bandwidth;
+
+        (t * scale).to_isize().unwrap()
+    }
+
+    fn frequency_support(&self) -> Option<Range<T>> {
+        let width = self.half_width();
+        Some(T::one() - width..T::one() + width)
+    }
+
+    /// `C M / sqrt(B) bspline(M C (u - 1) / B)`, the Fourier transform of the wavelet
+    /// with time in units of `1 / C`
+    fn frequency_response(&self, u: T) -> Option<T> {
+        let m: T = cast(f64::from(self.order));
+        let x = m * self.center * (u - T::one()) / self.bandwidth;
+        let spline: T = cast(bspline(self.order, x.to_f64().unwrap()));
+
+        Some(self.center * m / self.bandwidth.sqrt() * spline)
+    }
+
+    fn metadata_cache(&self) -> Option<&MetadataCache> {
+        Some(&self.metadata)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::wavelet::time_response;
+
+    #[test]
+    fn test_bspline() {
+        // Order 2 is the triangle and order 3 the quadratic spline
+        for x in [-1.2, -0.7, -0.3, 0.0, 0.4, 0.9] {
+            assert!((bspline(2, x) - (1.0 - f64::abs(x)).max(0.0)).abs() < 1e-12);
+        }
+        assert!((bspline(3, 0.0) - 0.75).abs() < 1e-12);
+        assert!((bspline(3, 1.0) - 0.125).abs() < 1e-12);
+        assert_eq!(bspline(1, 0.3), 1.0);
+        assert_eq!(bspline(1, 0.6), 0.0);
+
+        // Unit area for every order
+        for m in 1..6 {
+            let area: f64 = (-4000..4000)
+                .map(|k| bspline(m, (k as f64 + 0.5) / 1000.0))
+                .sum();
+            assert!((area / 1000.0 - 1.0).abs() < 1e-3);
+        }
+    }
+
+    #[test]
+    fn test_fbsp_wavelet_generate_mother() {
+        let size = 1024;
+        let mut fbsp = FbspWavelet::<f64>::new(2, 1.0, 1.5);
+        let mother = fbsp.generate_mother(size);
+
+        assert_eq!(mother.len(), size);
+
+        // Triangle of half width B / 2C = 1/3 around u = 1, peaking at C M / sqrt(B)
+        assert!((mother[size / 2] - 3.0).abs() < 1e-12);
+        let range = fbsp.frequency_support().unwrap();
+        for (j, &value) in mother.iter().enumerate() {
+            let u = 2.0 * j as f64 / size as f64;
+            assert_eq!(value > 0.0, range.contains(&u) && u != range.start, "u {u}");
+        }
+    }
+
+    #[test]
+    fn test_fbsp_wavelet_generate_time() {
+        let (m, b, c) = (2, 1.0, 1.5);
+        let fbsp = FbspWavelet::<f64>::new(m, b, c);
+        let size = 64;
+        let scale = 8.0;
+        let result = fbsp.generate(size, scale);
+        let width = fbsp.get_support(scale);
+
+        assert_eq!(result.len(), (width * 2 + 1) as usize);
+
+        // PyWavelets: sqrt(B) sinc(B t / M)^M e^(2 pi i C t), with the center frequency
+        // at 1 / scale cycles per sample
+        let pywt = |t: f64| {
+            let x = std::f64::consts::PI * b * t / f64::from(m);
+            let sinc = if x == 0.0 { 1.0 } else { x.sin() / x };
+            Complex::from_polar(
+                b.sqrt() * sinc.powi(m as i32),
+                std::f64::consts::TAU * c * t,
+            )
+        };
+        for (i, value) in result.iter().enumerate() {
+            let t = (i as isize - width) as f64 / scale / c;
+            assert!((value - pywt(t) * (size as f64 / scale)).norm() < 1e-9);
+        }
+
+        // Kernel is the inverse Fourier transform of the daughter frequency response
+        let response = |u: f64| fbsp.frequency_response(u).unwrap();
+        let range = fbsp.frequency_support().unwrap();
+        for (i, value) in result.iter().enumerate().step_by(7) {
+            let t = (i as isize - width) as f64 / scale;
+            let expected =
+                time_response(response, range.start, range.end, t) * (size as f64 / scale);
+            assert!((value - expected).norm() < 1e-3 * result[width as usize].norm());
+        }
+    }
+
+    #[test]
+    fn test_fbsp_wavelet_get_support() {
+        let fbsp = FbspWavelet::<f64>::new(2, 1.0, 1.5);
+        let scale = 10.0;
+        let width = fbsp.get_support(scale);
+
+        // The envelope is below 1e-3 of its peak beyond the support
+        let peak = fbsp.generate(64, scale)[width as usize].norm();
+        let outside = fbsp.time_response((width + 1) as f64 / scale) * (64.0 / scale);
+        assert!(outside.norm() < 1e-3 * peak);
+    }
+
+    #[test]
+    fn test_fbsp_wavelet_cwt_peak() {
+        crate::wavelet::tests::assert_cwt_peak(FbspWavelet::new(3, 0.5, 1.0), 3.0);
+    }
+}
@@ -0,0 +1,284 @@
+use crate::{cast, Complex, Float};
+
+use super::{frequency_cutoff, mother_len, time_response, time_support, MetadataCache, Wavelet};
+
+/// Generalized Morse wavelet (Lilly & Olhede, 2009), the default wavelet of MATLAB's `cwt`.
+///
+/// The frequency response is `a w^beta e^(-w^gamma)` for positive frequencies only, with
+/// `a = 2 (e gamma / beta)^(beta / gamma)` so that it peaks at 2. `gamma = 3` gives the
+/// most symmetric wavelets, and `gamma = 1` reduces to the Paul wavelet of order `beta`.
+pub struct MorseWavelet<T: Float = f32> {
+    gamma: T,
+    beta: T,
+    four_wavelen: T,
+    imag_frequency: bool,
+    double_sided: bool,
+    mother: Vec<T>,
+
+    norm: f64,
+    cutoff: f64,
+    support: T,
+    metadata: MetadataCache,
+}
+
+impl<T: Float> MorseWavelet<T> {
+    pub fn new(gamma: T, beta: T) -> Self {
+        assert!(gamma > T::zero(), "Morse wavelet gamma must be positive");
+        assert!(beta > T::zero(), "Morse wavelet beta must be positive");
+
+        let (g, b) = (gamma.to_f64().unwrap(), beta.to_f64().unwrap());
+        let norm = 2.0 * (std::f64::consts::E * g / b).powf(b / g);
+        let peak = (b / g).powf(1.0 / g);
+
+        let mut wavelet = Self {
+            gamma,
+            beta,
+            // Ratio of the Fourier wavelength, where s * |response(s w)|^2 peaks, to the peak period
+            four_wavelen: cast((2.0 * b / (2.0 * b + 1.0)).powf(1.0 / g)),
+            imag_frequency: false,
+            double_sided: false,
+            mother: vec![],
+            metadata: MetadataCache::default(),
+            norm,
+            cutoff: frequency_cutoff(|u| norm * (peak * u).powf(b) * (-(peak * u).powf(g)).exp()),
+            support: T::zero(),
+        };
+
+        // The time response has no closed form and decays slowly for small gamma,
+        // so the support extends to where it drops below 1e-3 of its peak
+        let min = 3.0 * wavelet.bandwidth().to_f64().unwrap();
+        wavelet.support = cast(time_support(|t| wavelet.time_response(t).norm(), min));
+
+        wavelet
+    }
+
+    /// Morse wavelet with a time-bandwidth product `P^2 = beta * gamma`
+    pub fn with_time_bandwidth(gamma: T, time_bandwidth: T) -> Self {
+        Self::new(gamma, time_bandwidth / gamma)
+    }
+
+    /// Symmetry parameter `gamma`
+    #[inline]
+    pub fn gamma(&self) -> T {
+        self.gamma
+    }
+
+    /// Decay or compactness parameter `beta`
+    #[inline]
+    pub fn beta(&self) -> T {
+        self.beta
+    }
+
+    /// Peak frequency `(beta / gamma)^(1 / gamma)` in radians per unit time of the mother wavelet.
+    ///
+    /// The mother is sampled so that its peak falls on `u = 1`, so scales map to frequencies
+    /// through the peak frequency regardless of `beta` and `gamma`.
+    pub fn peak_frequency(&self) -> T {
+        (self.beta / self.gamma).powf(T::one() / self.gamma)
+    }
+
+    /// Wavelet duration `P = sqrt(beta * gamma)`. `P / pi` is the number of oscillations
+    /// at the peak frequency that fit within the central window of the wavelet.
+    pub fn duration(&self) -> T {
+        (self.beta * self.gamma).sqrt()
+    }
+
+    /// Demodulate skewness `(gamma - 3) / P`, which is zero for the most symmetric wavelets
+    pub fn skewness(&self) -> T {
+        (self.gamma - cast(3.0)) / self.duration()
+    }
+
+    /// Frequency response at `u`, relative to the peak frequency
+    #[inline(always)]
+    fn response(&self, u: f64) -> f64 {
+        let w = self.peak_frequency().to_f64().unwrap() * u;
+        let (g, b) = (self.gamma.to_f64().unwrap(), self.beta.to_f64().unwrap());
+        self.norm * w.powf(b) * (-w.powf(g)).exp()
+    }
+
+    /// Time response at `t` peak periods
+    fn time_response(&self, t: f64) -> Complex<f64> {
+        time_response(|u| self.response(u), 0.0, self.cutoff, t)
+    }
+}
+
+impl<T: Float> Wavelet<T> for MorseWavelet<T> {
+    fn generate_mother(&mut self, size: usize) -> Vec<T> {
+        let len = mother_len(size, self.cutoff);
+        let step = 2.0 / size as f64;
+
+        let mother: Vec<T> = (0..len)
+            .map(|i| cast(self.response(i as f64 * step)))
+            .collect();
+
+        self.mother = mother.clone();
+
+        mother
+    }
+
+    fn generate(&self, size: usize, scale: T) -> Vec<Complex<T>> {
+        let width = self.get_support(scale);
+        let scale = scale.to_f64().unwrap();
+        let norm = size as f64 / scale;
+
+        (0..width * 2 + 1)
+            .map(|i| {
+                let value = self.time_response((i - width) as f64 / scale) * norm;
+                Complex::new(cast(value.re), cast(value.im))
+            })
+            .collect()
+    }
+
+    /// Time spread `P / 2pi` in units of the peak period, the standard deviation of the
+    /// Gaussian approximation to the wavelet near its peak frequency
+    #[inline(always)]
+    fn bandwidth(&self) -> T {
+        self.duration() / T::TAU()
+    }
+
+    #[inline(always)]
+    fn four_wavelen(&self) -> T {
+        self.four_wavelen
+    }
+
+    #[inline(always)]
+    fn imag_frequency(&self) -> bool {
+        self.imag_frequency
+    }
+
+    #[inline(always)]
+    fn is_double_sided(&self) -> bool {
+        self.double_sided
+    }
+
+    #[inline(always)]
+    fn mother(&self) -> &[T] {
+        self.mother.as_slice()
+    }
+
+    fn get_support(&self, scale: T) -> isize {
+        (self.support * scale).to_isize().unwrap()
+    }
+
+    #[inline(always)]
+    fn frequency_response(&self, u: T) -> Option<T> {
+        Some(cast(self.response(u.to_f64().unwrap())))
+    }
+
+    fn metadata_cache(&self) -> Option<&MetadataCache> {
+        Some(&self.metadata)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::PaulWavelet;
+
+    #[test]
+    fn test_morse_wavelet_parameters() {
+        let morse = MorseWavelet::<f64>::new(3.0, 20.0);
+
+        assert!((morse.peak_frequency() - (20.0f64 / 3.0).cbrt()).abs() < 1e-12);
+        assert!((morse.duration() - 60f64.sqrt()).abs() < 1e-12);
+        assert_eq!(morse.skewness(), 0.0);
+
+        let morse = MorseWavelet::<f64>::with_time_bandwidth(2.0, 60.0);
+        assert_eq!(morse.beta(), 30.0);
+        assert!((morse.skewness() + 1.0 / 60f64.sqrt()).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_morse_wavelet_generate_mother() {
+        let size = 1024;
+        let mut morse = MorseWavelet::<f64>::new(3.0, 20.0);
+        let mother = morse.generate_mother(size);
+
+        assert_eq!(mother.len(), mother_len(size, morse.cutoff));
+        assert_eq!(mother[0], 0.0);
+
+        // Bandpass normalized to a peak of 2 at u = 1
+        let peak = mother
+            .iter()
+            .enumerate()
+            .max_by(|a, b| a.1.total_cmp(b.1))
+            .unwrap();
+        assert_eq!(peak.0, size / 2);
+        assert!((peak.1 - 2.0).abs() < 1e-12);
+
+        let a = 2.0 * (std::f64::consts::E * 3.0 / 20.0).powf(20.0 / 3.0);
+        let w = morse.peak_frequency() * 0.5;
+        assert!((mother[size / 4] - a * w.powi(20) * (-w.powi(3)).exp()).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_morse_wavelet_matches_paul() {
+        // gamma = 1 is the Paul wavelet of order beta, up to normalization
+        let mut morse = MorseWavelet::<f64>::new(1.0, 4.0);
+        let mut paul = PaulWavelet::<f64>::new(4);
+
+        assert!((morse.four_wavelen() - paul.four_wavelen()).abs() < 1e-12);
+
+        let a = morse.generate_mother(256);
+        let b = paul.generate_mother(256);
+        let ratio = a[128] / b[128];
+        for (x, y) in a.iter().zip(b.iter()) {
+            assert!((x - y * ratio).abs() < 1e-9);
+        }
+
+        let a = morse.generate(64, 4.0);
+        let b = paul.generate(64, 4.0);
+        let offset = morse.get_support(4.0) - paul.get_support(4.0);
+        assert!(offset >= 0);
+        for (x, y) in a[offset as usize..].iter().zip(b.iter()) {
+            assert!((x - y * ratio).norm() < 1e-3 * a[a.len() / 2].norm());
+        }
+    }
+
+    #[test]
+    fn test_morse_wavelet_four_wavelen() {
+        // gamma = 2 matches the derivative of Gaussian wavelets
+        let morse = MorseWavelet::<f32>::new(2.0, 2.0);
+        assert!((morse.four_wavelen() - 0.894_427_2).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_morse_wavelet_peak_frequency() {
+        for (gamma, beta) in [(1.0, 4.0), (2.0, 2.0), (3.0, 20.0), (3.0, 60.0), (6.0, 9.0)] {
+            let mut morse = MorseWavelet::<f64>::new(gamma, beta);
+            let expected = (beta / gamma).powf(1.0 / gamma);
+            assert!((morse.peak_frequency() - expected).abs() < 1e-12);
+
+            // Maximum of the log response beta ln(w) - w^gamma, found by brute force
+            let step = 1e-5;
+            let peak = (1..500_000)
+                .map(|i| i as f64 * step)
+                .max_by(|&a, &b| {
+                    let response = |w: f64| beta * w.ln() - w.powf(gamma);
+                    response(a).total_cmp(&response(b))
+                })
+                .unwrap();
+            assert!(
+                (peak - expected).abs() < 2.0 * step,
+                "gamma {gamma} beta {beta}"
+            );
+
+            // The mother puts that peak on u = 1
+            let mother = morse.generate_mother(1024);
+            let index = mother
+                .iter()
+                .enumerate()
+                .max_by(|a, b| a.1.total_cmp(b.1))
+                .unwrap()
+                .0;
+            assert_eq!(index, 512, "gamma {gamma} beta {beta}");
+        }
+    }
+
+    #[test]
+    fn test_morse_wavelet_cwt_peak() {
+        for (gamma, beta) in [(1.0, 4.0), (3.0, 20.0), (3.0, 60.0), (6.0, 9.0)] {
+            crate::wavelet::tests::assert_cwt_peak(MorseWavelet::new(gamma, beta), 5.0);
+        }
+    }
+}
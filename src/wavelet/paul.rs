@@ -0,0 +1,299 @@
+use crate::{cast, Complex, Float};
+
+use super::{frequency_cutoff, mother_len, MetadataCache, Wavelet};
+
+/// Paul wavelet of order `m` (Torrence & Compo, 1998).
+///
+/// The frequency response is `(m u)^m e^(-m u)` for positive frequencies only, so the
+/// wavelet is analytic. Its slowly decaying time response gives good time localization of
+/// sharp transients at the cost of frequency resolution.
+pub struct PaulWavelet<T: Float = f32> {
+    order: u32,
+    four_wavelen: T,
+    imag_frequency: bool,
+    double_sided: bool,
+    mother: Vec<T>,
+
+    norm: T,
+    cutoff: f64,
+    metadata: MetadataCache,
+}
+
+/// Factorial as a float, only used for the small orders the wavelet supports
+fn factorial(n: u32) -> f64 {
+    (1..=n).map(f64::from).product()
+}
+
+impl<T: Float> PaulWavelet<T> {
+    pub fn new(order: u32) -> Self {
+        assert!(order > 0, "Paul wavelet order must be at least 1");
+
+        let m = f64::from(order);
+
+        // Torrence & Compo normalization, scaled by sqrt(2pi) like the Morlet mother
+        let norm = std::f64::consts::TAU.sqrt() * 2f64.powi(order as i32)
+            / (m * factorial(2 * order - 1)).sqrt();
+
+        Self {
+            order,
+            four_wavelen: cast(2.0 * m / (2.0 * m + 1.0)),
+            imag_frequency: false,
+            double_sided: false,
+            mother: vec![],
+            metadata: MetadataCache::default(),
+            norm: cast(norm),
+            cutoff: frequency_cutoff(|u| (m * u).powi(order as i32) * (-m * u).exp()),
+        }
+    }
+
+    /// Order `m` of the wavelet
+    #[inline]
+    pub fn order(&self) -> u32 {
+        self.order
+    }
+
+    /// Convert a time in units of scale to the dimensionless Torrence & Compo time `eta`
+    #[inline(always)]
+    fn eta(&self, t: T) -> T {
+        t * T::TAU() / cast(f64::from(self.order))
+    }
+
+    /// Frequency response at `u`, relative to the peak frequency
+    #[inline(always)]
+    fn response(&self, u: T) -> T {
+        let mu = cast::<T>(f64::from(self.order)) * u;
+        self.norm * mu.powi(self.order as i32) * (-mu).exp()
+    }
+}
+
+impl<T: Float> Wavelet<T> for PaulWavelet<T> {
+    fn generate_mother(&mut self, size: usize) -> Vec<T> {
+        let len = mother_len(size, self.cutoff);
+        let step: T = cast(2.0 / size as f64);
+
+        let mother: Vec<T> = (0..len)
+            .map(|i| self.response(cast::<T>(i as f64) * step))
+            .collect();
+
+        self.mother = mother.clone();
+
+        mother
+    }
+
+    fn generate(&self, size: usize, scale: T) -> Vec<Complex<T>> {
+        let width = self.get_support(scale);
+        let norm = cast::<T>(size as f64) * self.norm * cast(factorial(self.order - 1));
+        let power = -(self.order as i32 + 1);
+
+        let mut output: Vec<Complex<T>> = Vec::with_capacity((width * 2 + 1) as usize);
+
+        for i in 0..width * 2 + 1 {
+            let eta = self.eta(cast::<T>((i - width) as f64) / scale);
+            let value = Complex::new(T::one(), -eta).powi(power);
+
+            output.push(value * norm / scale);
+        }
+
+        output
+    }
+
+    /// E-folding time `s/sqrt(2)` of the wavelet power, in units of the peak period
+    #[inline(always)]
+    fn bandwidth(&self) -> T {
+        cast::<T>(f64::from(self.order)) / (T::TAU() * T::SQRT_2())
+    }
+
+    #[inline(always)]
+    fn four_wavelen(&self) -> T {
+        self.four_wavelen
+    }
+
+    #[inline(always)]
+    fn imag_frequency(&self) -> bool {
+        self.imag_frequency
+    }
+
+    #[inline(always)]
+    fn is_double_sided(&self) -> bool {
+        self.double_sided
+    }
+
+    #[inline(always)]
+    fn mother(&self) -> &[T] {
+        self.mother.as_slice()
+    }
+
+    /// The envelope `(1 + eta^2)^(-(m+1)/2)` drops to `1/e` at `eta^2 = e^(2/(m+1)) - 1`,
+    /// close to the `eta = 1/sqrt(2)` Torrence & Compo give for `m = 4`
+    fn efolding_time(&self) -> T {
+        let m = f64::from(self.order);
+        let eta = ((2.0 / (m + 1.0)).exp() - 1.0).sqrt();
+        cast(eta * m / std::f64::consts::TAU)
+    }
+
+    /// The envelope only decays as `(1 + eta^2)^(-(m+1)/2)`, so the support extends to
+    /// where it drops below 1e-3 of its peak rather than a multiple of the bandwidth.
+    fn get_support(&self, scale: T) -> isize {
+        let m = f64::from(self.order);
+        let eta = (1e3f64.powf(2.0 / (m + 1.0)) - 1.0).sqrt();
+        let t: T = cast(eta * m / std::f64::consts::TAU);
+
+        (t * scale).to_isize().unwrap()
+    }
+
+    #[inline(always)]
+    fn frequency_response(&self, u: T) -> Option<T> {
+        Some(self.response(u))
+    }
+
+    fn metadata_cache(&self) -> Option<&MetadataCache> {
+        Some(&self.metadata)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{FastCwt, LinFreqs};
+    use std::f64::consts::PI;
+
+    // Torrence & Compo (1998), table 1, in terms of the dimensionless frequency s*omega
+    fn reference_frequency(m: u32, s_omega: f64) -> f64 {
+        2f64.powi(m as i32) / (f64::from(m) * factorial(2 * m - 1)).sqrt()
+            * s_omega.powi(m as i32)
+            * (-s_omega).exp()
+    }
+
+    // Torrence & Compo (1998), table 1, without the constant i^m phase
+    fn reference_time(m: u32, eta: f64) -> Complex<f64> {
+        let norm = 2f64.powi(m as i32) * factorial(m) / (PI * factorial(2 * m)).sqrt();
+        Complex::new(1.0, -eta).powi(-(m as i32 + 1)) * norm
+    }
+
+    #[test]
+    fn test_paul_wavelet_generate_mother() {
+        let size = 1024;
+        let mut paul = PaulWavelet::<f64>::new(4);
+        let mother = paul.generate_mother(size);
+
+        assert_eq!(mother.len(), mother_len(size, paul.cutoff));
+        assert!(mother.len() > size);
+        assert!(mother[mother.len() - 1] < 1e-6 * mother[size / 2]);
+        assert_eq!(mother[0], 0.0);
+
+        // Sample j lies at s*omega = m * 2j/n
+        for (j, &value) in mother.iter().enumerate() {
+            let expected = (2.0 * PI).sqrt() * reference_frequency(4, 8.0 * j as f64 / size as f64);
+            assert!((value - expected).abs() < 1e-12);
+        }
+
+        // Peak at u = 1
+        let peak = mother
+            .iter()
+            .enumerate()
+            .max_by(|a, b| a.1.total_cmp(b.1))
+            .unwrap()
+            .0;
+        assert_eq!(peak, size / 2);
+    }
+
+    #[test]
+    fn test_paul_wavelet_generate_time() {
+        let paul = PaulWavelet::<f64>::new(4);
+        let size = 64;
+        let scale = 8.0;
+        let result = paul.generate(size, scale);
+        let width = paul.get_support(scale);
+
+        assert_eq!(result.len(), (width * 2 + 1) as usize);
+
+        // Same shape as the reference up to a constant factor
+        let ratio = result[width as usize] / reference_time(4, 0.0);
+        for (i, value) in result.iter().enumerate() {
+            let eta = (i as isize - width) as f64 / scale * 2.0 * PI / 4.0;
+            let expected = reference_time(4, eta) * ratio;
+            assert!((value - expected).norm() < 1e-9 * ratio.norm());
+        }
+
+        // Time kernel is the inverse Fourier transform of the daughter frequency response
+        let du = 1.0 / 1024.0;
+        for (i, value) in result.iter().enumerate() {
+            let t = (i as isize - width) as f64 / scale;
+            let expected: Complex<f64> = (1..16 * 1024)
+                .map(|j| {
+                    let u = j as f64 * du;
+                    let g = (2.0 * PI).sqrt() * reference_frequency(4, 4.0 * u);
+                    Complex::from_polar(g * du, 2.0 * PI * u * t)
+                })
+                .sum::<Complex<f64>>()
+                * (size as f64 / scale);
+
+            assert!((value - expected).norm() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn test_paul_wavelet_four_wavelen() {
+        // Fourier wavelength 4*pi*s/(2m+1) relative to the peak period 2*pi*s/m
+        for m in 1..8 {
+            let paul = PaulWavelet::<f64>::new(m);
+            let expected = 4.0 * PI / (2.0 * m as f64 + 1.0) / (2.0 * PI / m as f64);
+            assert!((paul.four_wavelen() - expected).abs() < 1e-12);
+        }
+
+        assert!((PaulWavelet::<f32>::new(4).four_wavelen() - 0.8888889).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_paul_wavelet_get_support() {
+        let paul = PaulWavelet::<f32>::new(4);
+
+        // (1 + eta^2)^(-5/2) = 1e-3 at eta = 3.85, or 2.45 peak periods
+        assert_eq!(paul.get_support(100.0), 245);
+    }
+
+    #[test]
+    fn test_paul_wavelet_cwt_peak() {
+        for m in [1, 2, 4, 8] {
+            crate::wavelet::tests::assert_cwt_peak(PaulWavelet::new(m), 5.0);
+        }
+    }
+
+    #[test]
+    fn test_paul_wavelet_cwt_analytic() {
+        let mut width = usize::MAX;
+        for m in [1, 2, 4, 8, 16] {
+            let scales = LinFreqs::new(1000, 10.0, 200.0, 96);
+            let mut fast_cwt = FastCwt::new(PaulWavelet::new(m), scales, true);
+            let result = fast_cwt.cwt(&crate::wavelet::tests::sine(75.0));
+
+            let power: Vec<f32> = result
+                .rows()
+                .map(|row| row[250..750].iter().map(|c| c.norm_sqr()).sum())
+                .collect();
+            let (peak, max) = power
+                .iter()
+                .copied()
+                .enumerate()
+                .max_by(|a, b| a.1.total_cmp(&b.1))
+                .unwrap();
+
+            // Only positive frequencies pass, so the sine comes out as a rotating phasor
+            // with a flat envelope instead of oscillating at twice its frequency
+            let row = &result.row(peak)[250..750];
+            let (min, max_norm) = row.iter().fold((f32::MAX, 0f32), |(lo, hi), c| {
+                (lo.min(c.norm()), hi.max(c.norm()))
+            });
+            assert!(max_norm - min < 1e-2 * max_norm, "order {m}");
+            for pair in row.windows(2) {
+                let phase = (pair[1] * pair[0].conj()).arg();
+                assert!((phase - 2.0 * std::f32::consts::PI * 0.075).abs() < 1e-3);
+            }
+
+            // The relative bandwidth narrows as the order grows
+            let half = power.iter().filter(|&&p| p > max / 2.0).count();
+            assert!(half < width, "order {m}");
+            width = half;
+        }
+    }
+}
@@ -0,0 +1,129 @@
+use std::ops::Range;
+
+use crate::{Complex, Float};
+
+use super::{FbspWavelet, MetadataCache, Wavelet};
+
+/// Shannon wavelet, matching PyWavelets' `shanB-C`.
+///
+/// The wavelet is `sqrt(B) sinc(B t) e^(2 pi i C t)`, whose frequency response is a box of
+/// width `B` centered on `C`. It is the frequency B-spline wavelet of order 1, with perfect
+/// frequency localization and a time response that only decays as `1 / t`.
+pub struct ShannonWavelet<T: Float = f32> {
+    inner: FbspWavelet<T>,
+}
+
+impl<T: Float> ShannonWavelet<T> {
+    /// Shannon wavelet with bandwidth `fb` and center frequency `fc`, as in PyWavelets'
+    /// `shan{fb}-{fc}`
+    pub fn new(fb: T, fc: T) -> Self {
+        Self {
+            inner: FbspWavelet::new(1, fb, fc),
+        }
+    }
+
+    /// Bandwidth `B`, the width of the frequency support
+    #[inline]
+    pub fn fb(&self) -> T {
+        self.inner.fb()
+    }
+
+    /// Center frequency `C`, mapped to `u = 1`
+    #[inline]
+    pub fn fc(&self) -> T {
+        self.inner.fc()
+    }
+}
+
+impl<T: Float> Wavelet<T> for ShannonWavelet<T> {
+    fn generate_mother(&mut self, size: usize) -> Vec<T> {
+        self.inner.generate_mother(size)
+    }
+
+    fn generate(&self, size: usize, scale: T) -> Vec<Complex<T>> {
+        self.inner.generate(size, scale)
+    }
+
+    #[inline(always)]
+    fn bandwidth(&self) -> T {
+        self.inner.bandwidth()
+    }
+
+    #[inline(always)]
+    fn four_wavelen(&self) -> T {
+        self.inner.four_wavelen()
+    }
+
+    #[inline(always)]
+    fn imag_frequency(&self) -> bool {
+        self.inner.imag_frequency()
+    }
+
+    #[inline(always)]
+    fn is_double_sided(&self) -> bool {
+        self.inner.is_double_sided()
+    }
+
+    #[inline(always)]
+    fn mother(&self) -> &[T] {
+        self.inner.mother()
+    }
+
+    fn get_support(&self, scale: T) -> isize {
+        self.inner.get_support(scale)
+    }
+
+    fn frequency_support(&self) -> Option<Range<T>> {
+        self.inner.frequency_support()
+    }
+
+    fn frequency_response(&self, u: T) -> Option<T> {
+        self.inner.frequency_response(u)
+    }
+
+    fn metadata_cache(&self) -> Option<&MetadataCache> {
+        self.inner.metadata_cache()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shannon_wavelet_generate_mother() {
+        let size = 1024;
+        let mut shannon = ShannonWavelet::<f64>::new(0.5, 1.0);
+        let mother = shannon.generate_mother(size);
+
+        // Box of height C / sqrt(B) for 0.75 < u < 1.25
+        for (j, &value) in mother.iter().enumerate() {
+            let u = 2.0 * j as f64 / size as f64;
+            let expected = if (u - 1.0).abs() < 0.25 {
+                2f64.sqrt()
+            } else {
+                0.0
+            };
+            assert!((value - expected).abs() < 1e-12, "u {u}");
+        }
+    }
+
+    #[test]
+    fn test_shannon_wavelet_generate_time() {
+        let (b, c) = (1.5, 1.0);
+        let shannon = ShannonWavelet::<f64>::new(b, c);
+        let size = 64;
+        let scale = 4.0;
+        let result = shannon.generate(size, scale);
+        let width = shannon.get_support(scale);
+
+        // PyWavelets: sqrt(B) sinc(B t) e^(2 pi i C t)
+        for (i, value) in result.iter().enumerate() {
+            let t = (i as isize - width) as f64 / scale / c;
+            let x = std::f64::consts::PI * b * t;
+            let sinc = if x == 0.0 { 1.0 } else { x.sin() / x };
+            let expected = Complex::from_polar(b.sqrt() * sinc, std::f64::consts::TAU * c * t);
+            assert!((value - expected * (size as f64 / scale)).norm() < 1e-9);
+        }
+    }
+}